@@ -1,6 +1,9 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -8,62 +11,1019 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use std::io;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, watch, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
-use crate::system::{SystemMonitor, SortOrder};
-use crate::ui;
+#[cfg(feature = "http")]
+use crate::metrics_server::MetricsServer;
+#[cfg(feature = "rhai")]
+use crate::rules::{RuleEvent, RulesEngine};
+#[cfg(feature = "lua")]
+use crate::script::{ScriptEngine, ScriptEvent};
+#[cfg(any(feature = "lua", feature = "rhai"))]
+use crate::system::AlertKind;
+
+use crate::column::ProcessColumn;
+use crate::config::{self, Theme};
+use crate::docker;
+use crate::filter::{FilterExpr, FilterMode};
+use crate::keybindings::{self, Action, Keybindings};
+use crate::system::{
+    build_process_tree, Alert, IoPriority, ProcessInfo, Signal, SortOrder, SortState, SystemMonitor, SystemSnapshot, DIALOG_SIGNALS, MAX_SWAPPINESS,
+    SIGKILL, SIGTERM,
+};
+use crate::ui::{self, HeaderLayout, LayoutPreset, PanelView, PortsTab, MAX_COLUMN_WIDTH, MIN_COLUMN_WIDTH};
+
+/// State for `--follow <pid>`: the root process plus every descendant seen since.
+pub struct FollowState {
+    pub root_pid: u32,
+    watchlist: HashSet<u32>,
+    pub last_root_info: Option<ProcessInfo>, // frozen stats shown once the root exits
+    pub root_exited: bool,
+    root_rx: Option<watch::Receiver<Option<ProcessInfo>>>,
+}
+
+impl FollowState {
+    pub fn new(root_pid: u32) -> Self {
+        Self {
+            root_pid,
+            watchlist: HashSet::from([root_pid]),
+            last_root_info: None,
+            root_exited: false,
+            root_rx: None,
+        }
+    }
+
+    pub fn watched_count(&self) -> usize {
+        self.watchlist.len()
+    }
+
+    /// `processes` is every live process, unfiltered: a descendant hidden by a filter is still followed.
+    pub fn update(&mut self, processes: &[ProcessInfo]) {
+        // exited pids go, a reused one would otherwise be taken for a descendant
+        let alive: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+        self.watchlist.retain(|pid| alive.contains(pid));
+
+        // repeat until nothing new is added, grandchildren can show up in the same tick as their parent
+        loop {
+            let before = self.watchlist.len();
+            for process in processes {
+                if self.watchlist.contains(&process.ppid) {
+                    self.watchlist.insert(process.pid);
+                }
+            }
+            if self.watchlist.len() == before {
+                break;
+            }
+        }
+
+        let root = match &self.root_rx {
+            Some(rx) => rx.borrow().clone(),
+            None => processes.iter().find(|p| p.pid == self.root_pid).cloned(),
+        };
+        match root {
+            Some(root) => self.last_root_info = Some(root),
+            None => self.root_exited = true,
+        }
+    }
+
+    /// Live watched processes in depth-first order, paired with their depth in the tree.
+    /// Children whose parent already exited get reparented, they show up as extra roots.
+    pub fn tree(&self, processes: &[ProcessInfo]) -> Vec<(usize, ProcessInfo)> {
+        let mut watched: Vec<ProcessInfo> = processes
+            .iter()
+            .filter(|p| self.watchlist.contains(&p.pid))
+            .cloned()
+            .collect();
+        watched.sort_by_key(|p| (p.pid != self.root_pid, p.pid));
+        build_process_tree(&watched)
+    }
+}
+
+// exit instead of showing the resize message forever
+const DEFAULT_RESIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The row one screen of `page` rows above `selected`, stopping at the first.
+pub fn page_up(selected: usize, page: usize) -> usize {
+    selected.saturating_sub(page.max(1))
+}
+
+/// The row one screen of `page` rows below `selected`, stopping at the last of `count`.
+pub fn page_down(selected: usize, page: usize, count: usize) -> usize {
+    (selected + page.max(1)).min(count.saturating_sub(1))
+}
+
+const MAX_ADAPTIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+fn log_alert(alert: &Alert) {
+    let (pid, name, value) = (alert.pid, &alert.name, alert.value);
+    warn!(pid, %name, value, message = %alert.message, "{} alert", alert.kind.name());
+}
+
+// "Alert at 14:03:22: message (name, PID 1234, value 97.5)"
+fn alert_line(alert: &Alert) -> String {
+    let age = chrono::Duration::from_std(alert.timestamp.elapsed()).unwrap_or_else(|_| chrono::Duration::zero());
+    let raised = chrono::Local::now() - age;
+    let mut line = format!("Alert at {}: {}", raised.format("%H:%M:%S"), alert.message);
+    if alert.pid != 0 {
+        line.push_str(&format!(" ({}, PID {}, value {})", alert.name, alert.pid, alert.value));
+    }
+    line
+}
+
+// back off while the machine is saturated so the monitor doesn't add to the load
+fn next_refresh_interval(current: Duration, configured: Duration, cpu_usage: f32) -> Duration {
+    if cpu_usage > 95.0 {
+        (current * 2).min(MAX_ADAPTIVE_INTERVAL.max(configured))
+    } else if cpu_usage < 80.0 {
+        configured
+    } else {
+        current
+    }
+}
+
+// alerts are recorded on the monitor for alerts_since, everything else goes straight to tx
+#[cfg(feature = "lua")]
+fn run_script(script: &ScriptEngine, monitor: &mut SystemMonitor, tx: &mpsc::UnboundedSender<String>) {
+    match script.on_refresh(monitor) {
+        Ok(events) => {
+            for event in events {
+                match event {
+                    ScriptEvent::Alert { message, pid, value } => monitor.raise_alert(AlertKind::Script, pid, value, message),
+                    ScriptEvent::Notify(message) => {
+                        let _ = tx.send(message);
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            tracing::warn!("script error: {:#}", err);
+            let _ = tx.send(format!("Script error: {:#}", err));
+        }
+    }
+}
+
+#[cfg(feature = "rhai")]
+fn run_rules(rules: &RulesEngine, monitor: &mut SystemMonitor, tx: &mpsc::UnboundedSender<String>) {
+    match rules.evaluate(monitor) {
+        Ok(events) => {
+            for event in events {
+                match event {
+                    RuleEvent::Alert { message, pid, value } => monitor.raise_alert(AlertKind::Rule, pid, value, message),
+                    RuleEvent::Notify(message) => {
+                        let _ = tx.send(message);
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            tracing::warn!("rules error: {:#}", err);
+            let _ = tx.send(format!("Rules error: {:#}", err));
+        }
+    }
+}
+
+// pending forever when there is no receiver, so the select! arm never fires
+async fn recv_optional<T>(rx: &mut Option<mpsc::UnboundedReceiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+// same for watch channels, yields the new value
+async fn changed_optional<T: Clone>(rx: &mut Option<watch::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => match rx.changed().await {
+            Ok(()) => Some(rx.borrow_and_update().clone()),
+            Err(_) => None,
+        },
+        None => std::future::pending().await,
+    }
+}
+
+async fn recv_snapshot(
+    rx: &mut Option<broadcast::Receiver<SystemSnapshot>>,
+) -> Result<SystemSnapshot, broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn write_csv_snapshot(snapshot: &SystemSnapshot) -> io::Result<()> {
+    let timestamp = snapshot.timestamp.to_rfc3339();
+    let mut out = io::stdout().lock();
+    for process in &snapshot.processes {
+        writeln!(out, "{},{}", timestamp, process.to_csv_row())?;
+    }
+    out.flush()
+}
+
+// written to the working directory on SIGUSR1
+const DUMP_PATH: &str = "systop_dump.json";
+
+fn write_json_snapshot(snapshot: &SystemSnapshot, path: &str) -> Result<()> {
+    let file = std::fs::File::create(path).with_context(|| format!("failed to create {}", path))?;
+    serde_json::to_writer_pretty(io::BufWriter::new(file), snapshot)
+        .with_context(|| format!("failed to write {}", path))
+}
+
+/// External control requests delivered as Unix signals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlSignal {
+    Dump,        // SIGUSR1
+    TogglePause, // SIGUSR2
+}
+
+#[cfg(unix)]
+fn spawn_signal_listener(tx: mpsc::UnboundedSender<ControlSignal>) -> Result<JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut usr1 = signal(SignalKind::user_defined1()).context("failed to listen for SIGUSR1")?;
+    let mut usr2 = signal(SignalKind::user_defined2()).context("failed to listen for SIGUSR2")?;
+    Ok(tokio::spawn(async move {
+        loop {
+            let signal = tokio::select! {
+                _ = usr1.recv() => ControlSignal::Dump,
+                _ = usr2.recv() => ControlSignal::TogglePause,
+            };
+            if tx.send(signal).is_err() {
+                break;
+            }
+        }
+    }))
+}
+
+/// Asks the Docker daemon for the running containers every `interval` on a blocking thread,
+/// the monitor is only locked to store the result.
+fn spawn_container_poller(monitor: Arc<RwLock<SystemMonitor>>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let socket = docker::socket_path();
+        let mut samples = HashMap::new();
+        loop {
+            let socket = socket.clone();
+            let poll = tokio::task::spawn_blocking(move || {
+                let containers = docker::list_containers(&socket, &mut samples);
+                (containers, samples)
+            });
+            let Ok((containers, polled_samples)) = poll.await else {
+                break;
+            };
+            samples = polled_samples;
+            monitor.write().await.set_containers(containers);
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
+
+/// Whether we run as root, or elevated on Windows.
+#[cfg(unix)]
+pub fn is_root() -> bool {
+    // SAFETY: geteuid has no preconditions and can't fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(windows)]
+pub fn is_root() -> bool {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
+    use winapi::um::securitybaseapi::GetTokenInformation;
+    use winapi::um::winnt::{TokenElevation, HANDLE, TOKEN_ELEVATION, TOKEN_QUERY};
+
+    // SAFETY: the token is only used after OpenProcessToken succeeded and closed after the
+    // query, GetTokenInformation writes at most size_of::<TOKEN_ELEVATION>() bytes
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return false;
+        }
+        let mut elevation: TOKEN_ELEVATION = std::mem::zeroed();
+        let mut size = 0;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut TOKEN_ELEVATION as *mut _,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        );
+        CloseHandle(token);
+        ok != 0 && elevation.TokenIsElevated != 0
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn is_root() -> bool {
+    false
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputKind {
+    Command,
+    Find,
+    OomScoreAdj(u32),
+    Nice(u32),
+    Swappiness,
+}
+
+/// A single-line text prompt rendered in the footer.
+#[derive(Debug, Clone)]
+pub struct InputPrompt {
+    pub kind: InputKind,
+    pub buffer: String,
+}
+
+impl InputPrompt {
+    pub fn new(kind: InputKind) -> Self {
+        Self {
+            kind,
+            buffer: String::new(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match &self.kind {
+            InputKind::Command => ":".to_string(),
+            InputKind::Find => "Jump to: ".to_string(),
+            InputKind::OomScoreAdj(pid) => format!("oom_score_adj for PID {} (-1000..1000): ", pid),
+            InputKind::Nice(pid) => format!("nice value for PID {} (-20..19): ", pid),
+            InputKind::Swappiness => format!("swappiness (0..{}): ", MAX_SWAPPINESS),
+        }
+    }
+}
+
+/// Checkbox list of cores for editing a process's CPU affinity.
+#[derive(Debug, Clone)]
+pub struct AffinityEditor {
+    pub pid: u32,
+    pub cpus: Vec<bool>, // indexed by cpu number
+    pub cursor: usize,
+}
+
+impl AffinityEditor {
+    pub fn new(pid: u32, cpu_count: usize, current: Option<&[u32]>) -> Self {
+        let cpus = (0..cpu_count)
+            .map(|cpu| current.is_none_or(|current| current.contains(&(cpu as u32))))
+            .collect();
+        Self { pid, cpus, cursor: 0 }
+    }
+
+    pub fn selected_cpus(&self) -> Vec<u32> {
+        self.cpus
+            .iter()
+            .enumerate()
+            .filter(|(_, &checked)| checked)
+            .map(|(cpu, _)| cpu as u32)
+            .collect()
+    }
+}
+
+/// List of I/O priority settings for a process, like a menu version of `ionice`.
+#[derive(Debug, Clone)]
+pub struct IoPriorityPicker {
+    pub pid: u32,
+    pub choices: Vec<IoPriority>,
+    pub cursor: usize,
+}
+
+impl IoPriorityPicker {
+    pub fn new(pid: u32, current: Option<IoPriority>) -> Self {
+        let choices = IoPriority::choices();
+        let cursor = current
+            .and_then(|current| choices.iter().position(|choice| *choice == current))
+            .unwrap_or(0);
+        Self { pid, choices, cursor }
+    }
+}
+
+/// Signal picker K opens for the selected process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillDialog {
+    pub pid: u32,
+    pub name: String,
+    pub cursor: usize, // into DIALOG_SIGNALS
+}
+
+impl KillDialog {
+    pub fn new(pid: u32, name: String) -> Self {
+        Self { pid, name, cursor: 0 }
+    }
+
+    pub fn signal(&self) -> Signal {
+        DIALOG_SIGNALS[self.cursor]
+    }
+
+    /// The question asked before the signal is sent.
+    pub fn prompt(&self) -> String {
+        match self.signal() {
+            SIGTERM | SIGKILL => format!("Kill {} (PID {})? [y/N]", self.name, self.pid),
+            signal => format!("Send {} to {} (PID {})? [y/N]", signal, self.name, self.pid),
+        }
+    }
+}
+
+/// What the main screen shows and where keys go first. Popups that sit on top of any of
+/// these (prompts, pickers, the legend) have their own fields in `AppState`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppMode {
+    Normal,
+    KillDialog(KillDialog),
+    KillConfirm(KillDialog), // signal picked, waiting for y
+    DetailView(u32), // pid shown in place of the table
+    FilterEntry,     // keys go to the filter text
+}
+
+/// Everything `ui::draw_ui` needs besides the monitor and the process list.
+pub struct AppState {
+    pub selected: usize,
+    pub scroll_offset: usize, // first table row on screen, kept in range by draw_ui
+    pub page_size: usize,     // table rows on screen, set by draw_ui
+    pub sort_state: SortState,
+    pub debug: bool,
+    pub layout: LayoutPreset,
+    pub ports_tab: Option<PortsTab>,
+    pub follow: Option<FollowState>,
+    pub input: Option<InputPrompt>, // Some while a footer prompt (command palette etc.) is open
+    pub status_message: Option<String>,
+    pub mode: AppMode,
+    pub affinity_editor: Option<AffinityEditor>,
+    pub io_priority_picker: Option<IoPriorityPicker>,
+    pub swap_view: bool, // table lists swap users instead of all processes
+    pub priority_view: bool, // table lists processes with a negative nice value
+    pub container_view: bool, // table lists Docker containers instead of processes
+    pub tree_mode: bool,     // the process table nests children under their parent
+    pub column_order: Vec<ProcessColumn>, // rearranged by dragging the column headers
+    pub column_widths: Vec<u16>,          // by ProcessColumn, changed by dragging the separators between headers
+    pub resize_hover: Option<ProcessColumn>, // column a drag from the separator under the mouse would resize
+    pub header_layout: Option<HeaderLayout>, // set by draw_ui while the process table is on screen
+    pub filter_mode: FilterMode,
+    pub filter_expr: Option<FilterExpr>, // compiled from filter_mode
+    pub filter_error: Option<String>,    // why the filter text doesn't compile, the previous one stays applied
+    pub show_legend: bool,
+    pub show_deleted: bool, // flag processes whose executable was deleted
+    pub selected_pids: HashSet<u32>, // marked with v for a batch kill
+    pub kill_confirm: Option<Vec<(u32, String)>>, // pids and names K is about to kill, waiting for y
+    pub paused: bool, // mirrors App::paused for the footer
+    pub auto_scroll: bool, // select the top row after every refresh while sorted by CPU or memory
+    pub panel_view: PanelView,
+    pub selected_interface: usize, // in the network panel
+    pub top_n: Option<usize>, // only list this many processes from the top of the sort order
+    pub diff_mode: bool, // the table lists what changed since baseline_snapshot
+    pub baseline_snapshot: Option<SystemSnapshot>,
+    pub theme: Theme,
+}
+
+impl AppState {
+    pub fn new(debug: bool) -> Self {
+        Self {
+            selected: 0,
+            scroll_offset: 0,
+            page_size: 1,
+            sort_state: SortState::new(SortOrder::Cpu),
+            debug,
+            layout: LayoutPreset::Standard,
+            ports_tab: None,
+            follow: None,
+            input: None,
+            status_message: None,
+            mode: AppMode::Normal,
+            affinity_editor: None,
+            io_priority_picker: None,
+            swap_view: false,
+            priority_view: false,
+            container_view: false,
+            tree_mode: false,
+            column_order: ProcessColumn::ALL.to_vec(),
+            column_widths: ProcessColumn::ALL.iter().map(|column| column.default_width()).collect(),
+            resize_hover: None,
+            header_layout: None,
+            filter_mode: FilterMode::None,
+            filter_expr: None,
+            filter_error: None,
+            show_legend: false,
+            show_deleted: false,
+            selected_pids: HashSet::new(),
+            kill_confirm: None,
+            paused: false,
+            auto_scroll: false,
+            panel_view: PanelView::Stats,
+            selected_interface: 0,
+            top_n: None,
+            diff_mode: false,
+            baseline_snapshot: None,
+            theme: Theme::default(),
+        }
+    }
+
+    /// The process the detail view shows, if it is open.
+    pub fn detail_pid(&self) -> Option<u32> {
+        match self.mode {
+            AppMode::DetailView(pid) => Some(pid),
+            _ => None,
+        }
+    }
+
+    /// Moves the kill dialog on for `code`: Enter on a signal asks for confirmation, then only y
+    /// goes ahead. Returns the dialog once confirmed, the signal is left to the caller to send.
+    pub fn handle_kill_key(&mut self, code: KeyCode) -> Option<KillDialog> {
+        match std::mem::replace(&mut self.mode, AppMode::Normal) {
+            AppMode::KillDialog(mut dialog) => {
+                match code {
+                    KeyCode::Up | KeyCode::Char('k') => dialog.cursor = dialog.cursor.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => dialog.cursor = (dialog.cursor + 1).min(DIALOG_SIGNALS.len() - 1),
+                    KeyCode::Esc => return None,
+                    KeyCode::Enter => {
+                        self.mode = AppMode::KillConfirm(dialog);
+                        return None;
+                    }
+                    _ => {}
+                }
+                self.mode = AppMode::KillDialog(dialog);
+                None
+            }
+            AppMode::KillConfirm(dialog) => (code == KeyCode::Char('y')).then_some(dialog),
+            other => {
+                self.mode = other;
+                None
+            }
+        }
+    }
+
+    /// Marks `pid` for a batch kill, or unmarks it if it already was.
+    pub fn toggle_mark(&mut self, pid: u32) {
+        if !self.selected_pids.remove(&pid) {
+            self.selected_pids.insert(pid);
+        }
+    }
+
+    /// Asks to confirm killing every marked process. Marks on processes that exited in the
+    /// meantime are dropped rather than listed.
+    pub fn confirm_batch_kill(&mut self, monitor: &SystemMonitor) {
+        let mut targets: Vec<(u32, String)> = self
+            .selected_pids
+            .iter()
+            .filter_map(|&pid| monitor.get_process_by_pid(pid).map(|p| (pid, p.name)))
+            .collect();
+        targets.sort();
+        self.selected_pids = targets.iter().map(|(pid, _)| *pid).collect();
+        if targets.is_empty() {
+            self.status_message = Some("The marked processes have all exited".to_string());
+        } else {
+            self.kill_confirm = Some(targets);
+        }
+    }
+}
 
 pub struct App {
     system_monitor: Arc<RwLock<SystemMonitor>>,
-    selected_process: usize,
-    sort_order: SortOrder,
+    state: AppState,
     last_update: Instant,
+    status_clears_on_refresh: bool, // the status message only lasts until the next refresh
     update_interval: Duration,
+    render_interval: Duration,
+    adaptive_interval: bool,
+    last_render: Option<Instant>,
     should_quit: bool,
-    debug_mode: bool,
+    cgroup_filter: Option<String>,
+    excluded_users: Vec<String>,
+    excluded_pids: HashSet<u32>,
+    show_kernel_threads: bool,
+    is_root: bool, // checked once at startup, gates the settings only root can change
+    watch_pid: Option<u32>, // the only process listed, we exit once it is gone
+    watched_exited: bool,
+    resize_timeout: Duration,
+    csv_export: bool, // headless only
+    too_small_since: Option<Instant>, // when the terminal dropped below the minimum size
+    #[cfg(feature = "http")]
+    metrics_addr: Option<std::net::SocketAddr>,
+    #[cfg(feature = "lua")]
+    script: Option<ScriptEngine>, // moved into the refresh task by run()
+    #[cfg(feature = "rhai")]
+    rules: Option<RulesEngine>, // same, evaluated after the script
+    script_rx: Option<mpsc::UnboundedReceiver<String>>, // status messages raised by the script or rules
+    signal_rx: Option<mpsc::UnboundedReceiver<ControlSignal>>,
+    paused: Arc<AtomicBool>, // the refresh task skips refreshes while set
+    refreshed: Arc<Notify>,  // poked by the refresh task after every refresh
+    wake: Arc<Notify>,       // cuts the refresh task's sleep short, e.g. to refresh right after resuming
+    keybindings: Keybindings,
+    column_drag: Option<(ProcessColumn, i16)>, // header being dragged and the last mouse x
+    column_resize: Option<(ProcessColumn, i16)>, // same for a separator, with the column being resized
+    saved_column_order: Vec<ProcessColumn>,    // what the config has, written again only when it differs
+    theme_rx: Option<watch::Receiver<Theme>>,
+    keys_rx: Option<watch::Receiver<(Keybindings, Vec<String>)>>, // with the entries that failed to parse
+    config_watcher: Option<notify::RecommendedWatcher>, // keeps theme_rx and keys_rx fed while alive
+    container_task: Option<JoinHandle<()>>, // polls the Docker daemon while the container view is open
+    alert_cursor: u64, // see SystemMonitor::alerts_since
 }
 
 impl App {
-    pub fn new(update_interval: Duration, debug: bool) -> Result<Self> {
+    /// `filter` starts the process list filtered, as if it had been typed in exclude mode.
+    pub fn new(update_interval: Duration, debug: bool, filter: Option<String>) -> Result<Self> {
         let system_monitor = Arc::new(RwLock::new(SystemMonitor::new()));
-        
+        let mut state = AppState::new(debug);
+        state.filter_mode = filter.map_or(FilterMode::None, FilterMode::Exclude);
+        state.filter_expr = state.filter_mode.compile()?;
+
         Ok(Self {
             system_monitor,
-            selected_process: 0,
-            sort_order: SortOrder::Cpu,
+            state,
             last_update: Instant::now(),
+            status_clears_on_refresh: false,
             update_interval,
+            render_interval: update_interval,
+            adaptive_interval: false,
+            last_render: None,
             should_quit: false,
-            debug_mode: debug,
+            cgroup_filter: None,
+            excluded_users: Vec::new(),
+            excluded_pids: HashSet::new(),
+            show_kernel_threads: false,
+            is_root: is_root(),
+            watch_pid: None,
+            watched_exited: false,
+            resize_timeout: DEFAULT_RESIZE_TIMEOUT,
+            csv_export: false,
+            too_small_since: None,
+            #[cfg(feature = "http")]
+            metrics_addr: None,
+            #[cfg(feature = "lua")]
+            script: None,
+            #[cfg(feature = "rhai")]
+            rules: None,
+            script_rx: None,
+            signal_rx: None,
+            paused: Arc::new(AtomicBool::new(false)),
+            refreshed: Arc::new(Notify::new()),
+            wake: Arc::new(Notify::new()),
+            keybindings: Keybindings::default(),
+            column_drag: None,
+            column_resize: None,
+            saved_column_order: ProcessColumn::ALL.to_vec(),
+            theme_rx: None,
+            keys_rx: None,
+            config_watcher: None,
+            container_task: None,
+            alert_cursor: 0,
         })
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        // setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
+    pub fn with_render_interval(mut self, render_interval: Duration) -> Self {
+        self.render_interval = render_interval;
+        self
+    }
+
+    pub fn with_adaptive_interval(mut self, adaptive: bool) -> Self {
+        self.adaptive_interval = adaptive;
+        self
+    }
+
+    /// Hides processes owned by any of `users`, matched against `ProcessInfo::user`.
+    pub fn with_excluded_users(mut self, users: Vec<String>) -> Self {
+        self.excluded_users = users;
+        self
+    }
+
+    pub fn with_excluded_pids(mut self, pids: impl IntoIterator<Item = u32>) -> Self {
+        self.excluded_pids.extend(pids);
+        self
+    }
+
+    pub fn with_cgroup_filter(mut self, cgroup: String) -> Self {
+        // "/a/b/" and "/a/b" name the same cgroup
+        let trimmed = cgroup.trim_end_matches('/');
+        self.cgroup_filter = Some(if trimmed.is_empty() { "/".to_string() } else { trimmed.to_string() });
+        self
+    }
+
+    /// How long to wait for the terminal to be resized above the minimum before giving up.
+    pub fn with_resize_timeout(mut self, timeout: Duration) -> Self {
+        self.resize_timeout = timeout;
+        self
+    }
+
+    /// Write every refresh as CSV to stdout in `run_headless`.
+    pub fn with_csv_export(mut self, enabled: bool) -> Self {
+        self.csv_export = enabled;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: SortOrder) -> Self {
+        self.state.sort_state = SortState::new(sort);
+        self
+    }
+
+    /// Start sorted by health score so the most concerning processes are on top.
+    pub fn with_alert_mode(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.state.sort_state = SortState::new(SortOrder::HealthScore);
+        }
+        self
+    }
+
+    /// Only list the first `n` processes in the current sort order, also for the CSV export.
+    pub fn with_top_n(mut self, n: usize) -> Self {
+        self.state.top_n = Some(n);
+        self
+    }
+
+    /// Flag processes still running a deleted executable, i.e. ones to restart after an upgrade.
+    pub fn with_show_deleted(mut self, enabled: bool) -> Self {
+        self.state.show_deleted = enabled;
+        self
+    }
+
+    /// Only list `pid` and quit once it exits.
+    pub fn with_watch_pid(mut self, pid: u32) -> Self {
+        self.watch_pid = Some(pid);
+        self
+    }
+
+    // after a refresh, true when the watched process is gone and we should stop
+    async fn watched_pid_exited(&mut self) -> bool {
+        let Some(pid) = self.watch_pid else {
+            return false;
+        };
+        self.watched_exited = !self.system_monitor.read().await.is_pid_alive(pid);
+        self.watched_exited
+    }
+
+    pub fn with_follow(mut self, pid: u32) -> Self {
+        self.state.follow = Some(FollowState::new(pid));
+        self
+    }
+
+    /// Serve Prometheus metrics on `addr` while the app runs.
+    #[cfg(feature = "http")]
+    pub fn with_metrics_addr(mut self, addr: std::net::SocketAddr) -> Self {
+        self.metrics_addr = Some(addr);
+        self
+    }
+
+    /// Loads the script right away so syntax errors show up before the terminal is taken over.
+    #[cfg(feature = "lua")]
+    pub fn with_script(mut self, path: &std::path::Path) -> Result<Self> {
+        self.script = Some(ScriptEngine::load(path)?);
+        Ok(self)
+    }
+
+    #[cfg(feature = "rhai")]
+    pub fn with_rules(mut self, path: &std::path::Path) -> Result<Self> {
+        self.rules = Some(RulesEngine::load(path)?);
+        Ok(self)
+    }
+
+    /// Loads the theme, keybindings and health score weights from the config file and follows
+    /// later edits to the first two. Does nothing when there is no config directory.
+    pub async fn config_hot_reload(&mut self) -> Result<()> {
+        let Some(path) = config::config_path() else {
+            return Ok(());
+        };
+        if path.exists() {
+            self.state.theme = config::load_theme(&path).unwrap_or_else(|err| {
+                warn!("using the default theme: {:#}", err);
+                Theme::default()
+            });
+            match config::load_keybindings(&path) {
+                Ok((keybindings, errors)) => {
+                    self.keybindings = keybindings;
+                    self.show_keybinding_errors(&errors);
+                }
+                Err(err) => warn!("using the default keybindings: {:#}", err),
+            }
+            match config::load_health_score_weights(&path) {
+                Ok(weights) => self.system_monitor.write().await.set_health_score_weights(weights),
+                Err(err) => warn!("using the default health score weights: {:#}", err),
+            }
+            match config::load_memory_pressure_weights(&path) {
+                Ok(weights) => self.system_monitor.write().await.set_memory_pressure_weights(weights),
+                Err(err) => warn!("using the default memory pressure weights: {:#}", err),
+            }
+            match config::load_column_widths(&path) {
+                Ok(widths) => {
+                    for (column, width) in widths {
+                        self.state.column_widths[column as usize] = width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+                    }
+                }
+                Err(err) => warn!("using the default column widths: {:#}", err),
+            }
+            match config::load_column_order(&path) {
+                Ok(Some(order)) => {
+                    self.state.column_order = order.clone();
+                    self.saved_column_order = order;
+                }
+                Ok(None) => {}
+                Err(err) => warn!("using the default column order: {:#}", err),
+            }
+        }
+        // watch even without a file yet, so creating one takes effect too
+        if path.parent().is_some_and(|dir| dir.is_dir()) {
+            let (theme_tx, theme_rx) = watch::channel(self.state.theme.clone());
+            let (keys_tx, keys_rx) = watch::channel((self.keybindings.clone(), Vec::new()));
+            self.config_watcher = Some(config::watch_config(&path, theme_tx, keys_tx)?);
+            self.theme_rx = Some(theme_rx);
+            self.keys_rx = Some(keys_rx);
+        }
+        Ok(())
+    }
+
+    /// Writes a commented TOML reference of every action and its default keys to `path`.
+    pub fn export_keybindings(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, keybindings::reference_toml())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn show_keybinding_errors(&mut self, errors: &[String]) {
+        if !errors.is_empty() {
+            self.state.status_message = Some(format!("Keybinding errors: {}", errors.join("; ")));
+        }
+    }
+
+    /// Starts the refresh loop plus the metrics server if configured. Script and rules
+    /// move into the refresh task, their messages arrive on `script_rx`. On Unix, SIGUSR1
+    /// and SIGUSR2 arrive on `signal_rx`.
+    fn start_background_tasks(&mut self) -> Vec<JoinHandle<()>> {
+        let mut tasks = Vec::new();
+
+        #[cfg(unix)]
+        {
+            let (tx, rx) = mpsc::unbounded_channel();
+            match spawn_signal_listener(tx) {
+                Ok(task) => {
+                    tasks.push(task);
+                    self.signal_rx = Some(rx);
+                }
+                Err(err) => warn!("{:#}", err),
+            }
+        }
+
+        #[cfg(feature = "http")]
+        if let Some(addr) = self.metrics_addr {
+            tasks.push(MetricsServer::new(addr, Arc::clone(&self.system_monitor)).run());
+        }
 
         // spawn background task for system updates
         let monitor_clone = Arc::clone(&self.system_monitor);
-        let interval = self.update_interval;
-        tokio::spawn(async move {
+        let configured_interval = self.update_interval;
+        let adaptive = self.adaptive_interval;
+        let paused = Arc::clone(&self.paused);
+        let refreshed = Arc::clone(&self.refreshed);
+        let wake = Arc::clone(&self.wake);
+        #[cfg(any(feature = "lua", feature = "rhai"))]
+        let script_tx = {
+            let (tx, rx) = mpsc::unbounded_channel::<String>();
+            self.script_rx = Some(rx);
+            tx
+        };
+        #[cfg(feature = "lua")]
+        let script = self.script.take();
+        #[cfg(feature = "rhai")]
+        let rules = self.rules.take();
+        tasks.push(tokio::spawn(async move {
+            let mut interval = configured_interval;
             loop {
-                {
+                if paused.load(Ordering::Relaxed) {
+                    tokio::select! {
+                        _ = tokio::time::sleep(interval) => {}
+                        _ = wake.notified() => {}
+                    }
+                    continue;
+                }
+
+                let cpu_usage = {
                     let mut monitor = monitor_clone.write().await;
                     monitor.refresh();
+                    #[cfg(feature = "lua")]
+                    if let Some(script) = &script {
+                        run_script(script, &mut monitor, &script_tx);
+                    }
+                    #[cfg(feature = "rhai")]
+                    if let Some(rules) = &rules {
+                        run_rules(rules, &mut monitor, &script_tx);
+                    }
+                    monitor.get_total_cpu_usage()
+                };
+                refreshed.notify_one();
+
+                if adaptive {
+                    let next = next_refresh_interval(interval, configured_interval, cpu_usage);
+                    if next != interval {
+                        info!(
+                            cpu_usage,
+                            from_ms = interval.as_millis() as u64,
+                            to_ms = next.as_millis() as u64,
+                            "adjusting refresh interval"
+                        );
+                        interval = next;
+                    }
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = wake.notified() => {}
                 }
-                tokio::time::sleep(interval).await;
             }
-        });
+        }));
+
+        tasks
+    }
+
+    /// Monitors without a TUI until Ctrl-C: refreshes, serves metrics and runs the script
+    /// and rules if configured. With CSV export on, every refresh is written to stdout.
+    pub async fn run_headless(&mut self) -> Result<()> {
+        // subscribe before the first refresh so it isn't missed
+        let mut snapshots = if self.csv_export {
+            let mut out = io::stdout().lock();
+            writeln!(out, "timestamp,{}", ProcessInfo::csv_header())?;
+            Some(self.system_monitor.read().await.subscribe())
+        } else {
+            None
+        };
+        let tasks = self.start_background_tasks();
+        let mut script_rx = self.script_rx.take();
+        let mut signal_rx = self.signal_rx.take();
+        let refreshed = Arc::clone(&self.refreshed);
+
+        let result = loop {
+            tokio::select! {
+                signal = tokio::signal::ctrl_c() => break signal.context("failed to listen for Ctrl-C"),
+                _ = refreshed.notified() => {
+                    for alert in self.system_monitor.read().await.alerts_since(&mut self.alert_cursor) {
+                        log_alert(&alert);
+                    }
+                    if self.watched_pid_exited().await {
+                        break Ok(());
+                    }
+                }
+                Some(message) = recv_optional(&mut script_rx) => info!(%message, "script message"),
+                Some(signal) = recv_optional(&mut signal_rx) => {
+                    let message = self.handle_signal(signal).await;
+                    info!(%message, "control signal");
+                }
+                snapshot = recv_snapshot(&mut snapshots) => match snapshot {
+                    Ok(mut snapshot) => {
+                        if let Some(n) = self.state.top_n {
+                            self.state.sort_state.sort(&mut snapshot.processes);
+                            snapshot.processes.truncate(n);
+                        }
+                        match write_csv_snapshot(&snapshot) {
+                            Ok(()) => {}
+                            // reader went away (e.g. piped into head), that's a normal way to stop
+                            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => break Ok(()),
+                            Err(err) => break Err(err).context("failed to write CSV"),
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "csv export fell behind, dropped snapshots");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break Ok(()),
+                },
+            }
+        };
+
+        for task in tasks {
+            task.abort();
+        }
+        self.report_watched_exit();
+        result
+    }
+
+    fn report_watched_exit(&self) {
+        if let Some(pid) = self.watch_pid.filter(|_| self.watched_exited) {
+            eprintln!("Process {} exited", pid);
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        // setup terminal
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        if let Some(follow) = self.state.follow.as_mut() {
+            let mut monitor = self.system_monitor.write().await;
+            follow.root_rx = Some(monitor.watch_pid(follow.root_pid));
+        }
+
+        if let Err(err) = self.config_hot_reload().await {
+            warn!("config hot reload disabled: {:#}", err);
+        }
+        let tasks = self.start_background_tasks();
 
         // main event loop
         let res = self.run_app(&mut terminal).await;
+        for task in tasks.into_iter().chain(self.container_task.take()) {
+            task.abort();
+        }
 
         // restore terminal
         disable_raw_mode()?;
@@ -73,48 +1033,82 @@ impl App {
             DisableMouseCapture
         )?;
         terminal.show_cursor()?;
+        // only now that the alternate screen is gone
+        self.report_watched_exit();
 
-        if let Err(err) = res {
-            println!("{:?}", err)
-        }
-
-        Ok(())
+        res
     }
 
     async fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let mut events = EventStream::new();
+        let mut script_rx = self.script_rx.take();
+        let mut signal_rx = self.signal_rx.take();
+        let mut theme_rx = self.theme_rx.take();
+        let mut keys_rx = self.keys_rx.take();
+        let refreshed = Arc::clone(&self.refreshed);
+
         loop {
-            self.draw(terminal).await?;
-
-            // handle events with timeout to allow for regular redraws
-            if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('q') => {
-                            self.should_quit = true;
-                        }
-                        KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => {
-                            self.should_quit = true;
-                        }
-                        KeyCode::Up | KeyCode::Char('k') => {
-                            self.move_selection_up().await;
-                        }
-                        KeyCode::Down | KeyCode::Char('j') => {
-                            self.move_selection_down().await;
-                        }
-                        KeyCode::Char('K') => {
-                            self.kill_selected_process().await?;
-                        }
-                        KeyCode::Char('c') => {
-                            self.sort_order = SortOrder::Cpu;
-                            self.selected_process = 0;
-                        }
-                        KeyCode::Char('m') => {
-                            self.sort_order = SortOrder::Memory;
-                            self.selected_process = 0;
-                        }
-                        _ => {}
+            self.update_follow().await;
+            self.check_terminal_size(terminal)?;
+            let render_due = self
+                .last_render
+                .is_none_or(|last| last.elapsed() >= self.render_interval);
+            if render_due {
+                self.draw(terminal).await?;
+                self.last_render = Some(Instant::now());
+            }
+            // wake up for the next redraw even if nothing else happens
+            let until_render = self
+                .last_render
+                .map_or(Duration::ZERO, |last| self.render_interval.saturating_sub(last.elapsed()));
+
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(Ok(event)) => self.handle_event(event).await?,
+                    Some(Err(err)) => return Err(err).context("failed to read terminal events"),
+                    None => break,
+                },
+                // new data, drawn once the render cap allows
+                _ = refreshed.notified() => {
+                    if self.watched_pid_exited().await {
+                        self.should_quit = true;
+                    }
+                    if std::mem::take(&mut self.status_clears_on_refresh) {
+                        self.state.status_message = None;
+                        self.last_render = None;
+                    }
+                    if self.state.auto_scroll
+                        && matches!(self.state.sort_state.order, SortOrder::Cpu | SortOrder::Memory)
+                    {
+                        self.state.selected = 0;
+                    }
+                    let alerts = self.system_monitor.read().await.alerts_since(&mut self.alert_cursor);
+                    for alert in &alerts {
+                        log_alert(alert);
+                    }
+                    if let Some(alert) = alerts.last() {
+                        self.state.status_message = Some(alert_line(alert));
+                        self.last_render = None;
                     }
                 }
+                Some(message) = recv_optional(&mut script_rx) => {
+                    self.state.status_message = Some(message);
+                    self.last_render = None;
+                }
+                Some(signal) = recv_optional(&mut signal_rx) => {
+                    self.state.status_message = Some(self.handle_signal(signal).await);
+                    self.last_render = None;
+                }
+                Some(theme) = changed_optional(&mut theme_rx) => {
+                    self.state.theme = theme;
+                    self.last_render = None;
+                }
+                Some((keybindings, errors)) = changed_optional(&mut keys_rx) => {
+                    self.keybindings = keybindings;
+                    self.show_keybinding_errors(&errors);
+                    self.last_render = None;
+                }
+                _ = tokio::time::sleep(until_render) => {}
             }
 
             if self.should_quit {
@@ -124,60 +1118,856 @@ impl App {
         Ok(())
     }
 
+    async fn handle_event(&mut self, event: Event) -> Result<()> {
+        match event {
+            Event::Key(key) => {
+                // input should show up immediately regardless of the render cap
+                self.last_render = None;
+
+                // once the followed process is gone any key exits
+                if self.state.follow.as_ref().is_some_and(|f| f.root_exited) {
+                    self.should_quit = true;
+                } else if self.state.show_legend {
+                    self.state.show_legend = false;
+                } else if let Some(targets) = self.state.kill_confirm.take() {
+                    // anything but y cancels
+                    if key.code == KeyCode::Char('y') {
+                        self.kill_marked(&targets).await;
+                    }
+                } else if matches!(self.state.mode, AppMode::KillDialog(_) | AppMode::KillConfirm(_)) {
+                    if let Some(dialog) = self.state.handle_kill_key(key.code) {
+                        self.send_signal(dialog.pid, &dialog.name, dialog.signal()).await;
+                    }
+                } else if self.state.input.is_some() {
+                    self.handle_input_key(key.code).await;
+                } else if self.state.mode == AppMode::FilterEntry {
+                    self.handle_filter_key(key.code);
+                } else if self.state.affinity_editor.is_some() {
+                    self.handle_affinity_key(key.code).await;
+                } else if self.state.io_priority_picker.is_some() {
+                    self.handle_io_priority_key(key.code).await;
+                } else {
+                    self.handle_key(key).await?;
+                }
+            }
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            // crossterm turns SIGWINCH into resize events, redraw at the new size right away
+            Event::Resize(..) => self.last_render = None,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // dragging a column header over another one moves it there, dragging the separator between
+    // two resizes them. Both are saved to the config when the button is released
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let Some(header) = &self.state.header_layout else {
+            return;
+        };
+        // the name column fills the rest of the row, its right edge resizes its neighbour instead
+        let resized = header.separator_at(mouse.column, mouse.row).map(|(left, right)| {
+            if left == ProcessColumn::Name { right } else { left }
+        });
+        match mouse.kind {
+            MouseEventKind::Moved if resized != self.state.resize_hover => {
+                self.state.resize_hover = resized;
+                self.last_render = None;
+            }
+            MouseEventKind::Down(MouseButton::Left) if resized.is_some() => {
+                self.column_resize = resized.map(|column| (column, mouse.column as i16));
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.column_drag = header
+                    .column_at(mouse.column, mouse.row)
+                    .map(|column| (column, mouse.column as i16));
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.column_resize.is_some() => {
+                let Some((column, last_x)) = self.column_resize else {
+                    return;
+                };
+                let x = mouse.column as i16;
+                // right of the name column the separator is the resized column's left edge
+                let grows_right = header
+                    .cells
+                    .iter()
+                    .position(|(_, _, c)| *c == column)
+                    .is_some_and(|i| i == 0 || header.cells[i - 1].2 != ProcessColumn::Name);
+                let delta = if grows_right { x - last_x } else { last_x - x };
+                let width = &mut self.state.column_widths[column as usize];
+                *width = (*width as i16 + delta).clamp(MIN_COLUMN_WIDTH as i16, MAX_COLUMN_WIDTH as i16) as u16;
+                self.column_resize = Some((column, x));
+                self.last_render = None;
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some((column, _)) = self.column_drag else {
+                    return;
+                };
+                // only the x counts once dragging, the pointer may drift off the header row
+                if let Some(target) = header.column_at(mouse.column, header.y).filter(|target| *target != column) {
+                    let order = &mut self.state.column_order;
+                    if let (Some(from), Some(to)) = (
+                        order.iter().position(|c| *c == column),
+                        order.iter().position(|c| *c == target),
+                    ) {
+                        order.remove(from);
+                        order.insert(to, column);
+                        self.last_render = None;
+                    }
+                }
+                self.column_drag = Some((column, mouse.column as i16));
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if self.column_resize.take().is_some() {
+                    self.save_column_widths();
+                }
+                let dragged = self.column_drag.take().is_some();
+                if dragged && self.state.column_order != self.saved_column_order {
+                    self.save_column_order();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn save_column_widths(&mut self) {
+        let Some(path) = config::config_path() else {
+            return;
+        };
+        // only what differs from the defaults, so a later default change still applies
+        let widths: Vec<(ProcessColumn, u16)> = ProcessColumn::ALL
+            .into_iter()
+            .map(|column| (column, self.state.column_widths[column as usize]))
+            .filter(|(column, width)| *width != column.default_width())
+            .collect();
+        if let Err(err) = config::save_column_widths(&path, &widths) {
+            self.state.status_message = Some(format!("{:#}", err));
+        }
+    }
+
+    fn save_column_order(&mut self) {
+        let Some(path) = config::config_path() else {
+            return;
+        };
+        match config::save_column_order(&path, &self.state.column_order) {
+            Ok(()) => self.saved_column_order = self.state.column_order.clone(),
+            Err(err) => self.state.status_message = Some(format!("{:#}", err)),
+        }
+    }
+
+    /// Acts on a SIGUSR1/SIGUSR2 and describes what happened.
+    async fn handle_signal(&mut self, signal: ControlSignal) -> String {
+        match signal {
+            ControlSignal::Dump => {
+                let snapshot = self.system_monitor.read().await.snapshot();
+                match write_json_snapshot(&snapshot, DUMP_PATH) {
+                    Ok(()) => format!("Dumped state to {}", DUMP_PATH),
+                    Err(err) => format!("Dump failed: {:#}", err),
+                }
+            }
+            ControlSignal::TogglePause => {
+                if self.toggle_pause() {
+                    "Paused, send SIGUSR2 to resume".to_string()
+                } else {
+                    "Resumed".to_string()
+                }
+            }
+        }
+    }
+
+    /// Pauses or resumes the refresh task, returns whether it is now paused.
+    fn toggle_pause(&mut self) -> bool {
+        // fetch_xor returns the previous value
+        let paused = !self.paused.fetch_xor(true, Ordering::Relaxed);
+        if !paused {
+            // don't leave the data stale until the next tick
+            self.wake.notify_one();
+        }
+        self.state.paused = paused;
+        paused
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.state.status_message = None;
+        self.status_clears_on_refresh = false;
+
+        if let AppMode::DetailView(pid) = self.state.mode {
+            match key.code {
+                KeyCode::Esc => {
+                    self.state.mode = AppMode::Normal;
+                    return Ok(());
+                }
+                KeyCode::Char('O') => {
+                    self.state.input = Some(InputPrompt::new(InputKind::OomScoreAdj(pid)));
+                    return Ok(());
+                }
+                KeyCode::Char('I') => {
+                    let current = self.system_monitor.read().await.get_process_by_pid(pid).and_then(|p| p.io_priority);
+                    self.state.io_priority_picker = Some(IoPriorityPicker::new(pid, current));
+                    return Ok(());
+                }
+                KeyCode::Char('A') => {
+                    let monitor = self.system_monitor.read().await;
+                    let current = monitor.get_process_by_pid(pid).and_then(|p| p.cpu_affinity);
+                    self.state.affinity_editor = Some(AffinityEditor::new(
+                        pid,
+                        monitor.get_cpu_info().len(),
+                        current.as_deref(),
+                    ));
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        if key.code == KeyCode::Esc && self.state.filter_mode != FilterMode::None {
+            self.state.filter_mode = FilterMode::None;
+            self.compile_filter();
+            self.state.selected = 0;
+            return Ok(());
+        }
+
+        let Some(action) = self.keybindings.action(&key) else {
+            return Ok(());
+        };
+        match action {
+            Action::Quit => {
+                self.should_quit = true;
+            }
+            Action::ToggleKernelThreads => {
+                self.show_kernel_threads = !self.show_kernel_threads;
+            }
+            // moving by hand means the user wants to look at something else, stop following the top
+            Action::MoveUp => {
+                self.state.auto_scroll = false;
+                self.move_selection_up().await;
+            }
+            Action::MoveDown => {
+                self.state.auto_scroll = false;
+                self.move_selection_down().await;
+            }
+            Action::PageUp => {
+                self.move_selection_page_up().await;
+            }
+            Action::PageDown => {
+                self.move_selection_page_down().await;
+            }
+            Action::SelectFirst => {
+                self.move_selection_home().await;
+            }
+            Action::SelectLast => {
+                self.move_selection_end().await;
+            }
+            Action::KillProcess if !self.state.selected_pids.is_empty() => {
+                self.confirm_batch_kill().await;
+            }
+            Action::KillProcess => {
+                self.open_kill_dialog().await;
+            }
+            Action::ToggleMark => {
+                self.toggle_mark_selected().await;
+            }
+            Action::Renice => {
+                let monitor = self.system_monitor.read().await;
+                let pid = self.visible_processes(&monitor).get(self.state.selected).map(|p| p.pid);
+                drop(monitor);
+                if let Some(pid) = pid {
+                    self.state.input = Some(InputPrompt::new(InputKind::Nice(pid)));
+                }
+            }
+            Action::SortCpu => self.set_sort(SortOrder::Cpu),
+            Action::SortMemory => self.set_sort(SortOrder::Memory),
+            Action::SortOomScore => self.set_sort(SortOrder::OomScore),
+            Action::SortMajorFaults => self.set_sort(SortOrder::MajorFaults),
+            Action::ToggleTreeView => {
+                self.state.tree_mode = !self.state.tree_mode;
+                self.state.selected = 0;
+            }
+            Action::ToggleSwapView => {
+                self.state.swap_view = !self.state.swap_view;
+                self.state.priority_view = false;
+                self.state.selected = 0;
+                self.system_monitor.write().await.set_collect_swap(self.state.swap_view);
+            }
+            Action::TogglePriorityView => {
+                self.state.priority_view = !self.state.priority_view;
+                self.state.swap_view = false;
+                self.state.selected = 0;
+                self.system_monitor.write().await.set_collect_swap(false);
+            }
+            Action::SortMemoryEfficiency => self.set_sort(SortOrder::MemoryEfficiency),
+            Action::SortHealthScore => self.set_sort(SortOrder::HealthScore),
+            Action::SortThreads => self.set_sort(SortOrder::Threads),
+            Action::SortDiskRead => self.set_sort(SortOrder::DiskRead),
+            Action::SortDiskWrite => self.set_sort(SortOrder::DiskWrite),
+            Action::SortUser => self.set_sort(SortOrder::User),
+            Action::SortStartTime => self.set_sort(SortOrder::StartTime),
+            Action::SortNice => self.set_sort(SortOrder::Nice),
+            Action::SortGpu => self.set_sort(SortOrder::GpuUsage),
+            Action::OpenDetail => {
+                self.open_detail().await;
+            }
+            Action::TogglePorts => {
+                self.toggle_ports_view().await;
+            }
+            Action::CycleLayout => {
+                self.cycle_layout();
+            }
+            Action::ResetHistory if self.state.debug => {
+                self.system_monitor.write().await.reset_history();
+            }
+            Action::ResetHistory => {}
+            Action::EditSwappiness => {
+                if self.is_root {
+                    self.state.input = Some(InputPrompt::new(InputKind::Swappiness));
+                } else {
+                    self.state.status_message = Some("Requires root to change swappiness".to_string());
+                }
+            }
+            Action::CommandPalette => {
+                self.state.input = Some(InputPrompt::new(InputKind::Command));
+            }
+            Action::FindProcess => {
+                self.state.input = Some(InputPrompt::new(InputKind::Find));
+            }
+            Action::ToggleDiffMode => {
+                self.state.diff_mode = !self.state.diff_mode;
+                self.state.baseline_snapshot = if self.state.diff_mode {
+                    Some(self.system_monitor.read().await.snapshot())
+                } else {
+                    None
+                };
+                self.state.selected = 0;
+            }
+            Action::ShowLegend => {
+                self.state.show_legend = true;
+            }
+            Action::TogglePause => {
+                self.toggle_pause();
+            }
+            Action::ToggleAutoScroll => {
+                self.state.auto_scroll = !self.state.auto_scroll;
+            }
+            Action::CycleInterface => {
+                let count = self.system_monitor.read().await.get_network_info().len();
+                if self.state.panel_view != PanelView::Network {
+                    self.state.panel_view = PanelView::Network;
+                    self.state.selected_interface = 0;
+                } else if self.state.selected_interface + 1 < count {
+                    self.state.selected_interface += 1;
+                } else {
+                    self.state.panel_view = PanelView::Stats;
+                }
+            }
+            Action::ToggleContainers => {
+                self.state.container_view = !self.state.container_view;
+                if let Some(task) = self.container_task.take() {
+                    task.abort();
+                }
+                if self.state.container_view {
+                    self.container_task = Some(spawn_container_poller(Arc::clone(&self.system_monitor), self.update_interval));
+                } else {
+                    self.system_monitor.write().await.clear_containers();
+                }
+            }
+            Action::CycleView => {
+                self.state.panel_view = self.state.panel_view.next();
+                self.state.status_message = Some(format!("View: {}", self.state.panel_view.name()));
+            }
+            Action::HideProcess => {
+                self.hide_selected_process().await;
+            }
+            Action::SearchProcesses => self.start_filter(FilterMode::Search),
+            Action::FilterProcesses => self.start_filter(FilterMode::Exclude),
+            Action::RegexFilter => self.start_filter(FilterMode::Regex),
+        }
+
+        Ok(())
+    }
+
+    async fn handle_affinity_key(&mut self, code: KeyCode) {
+        let Some(editor) = self.state.affinity_editor.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => editor.cursor = editor.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                editor.cursor = (editor.cursor + 1).min(editor.cpus.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(checked) = editor.cpus.get_mut(editor.cursor) {
+                    *checked = !*checked;
+                }
+            }
+            KeyCode::Esc => self.state.affinity_editor = None,
+            KeyCode::Enter => {
+                if let Some(editor) = self.state.affinity_editor.take() {
+                    let cpus = editor.selected_cpus();
+                    let result = self.system_monitor.read().await.set_cpu_affinity(editor.pid, &cpus);
+                    self.state.status_message = Some(match result {
+                        Ok(()) => format!("PID {} pinned to CPUs {}", editor.pid, ui::format_cpu_list(&cpus)),
+                        Err(err) => format!("{:#}", err),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // switching modes keeps the text typed so far
+    fn start_filter(&mut self, mode: fn(String) -> FilterMode) {
+        let text = self.state.filter_mode.text().unwrap_or_default().to_string();
+        self.state.filter_mode = mode(text);
+        self.state.mode = AppMode::FilterEntry;
+        self.compile_filter();
+    }
+
+    // the list narrows (or the highlight moves) as the filter is typed, Enter keeps it and Esc drops it
+    fn handle_filter_key(&mut self, code: KeyCode) {
+        let Some(filter) = self.state.filter_mode.text_mut() else {
+            self.state.mode = AppMode::Normal;
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => filter.push(c),
+            KeyCode::Backspace => {
+                filter.pop();
+            }
+            KeyCode::Esc => {
+                self.state.filter_mode = FilterMode::None;
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                if filter.is_empty() {
+                    self.state.filter_mode = FilterMode::None;
+                }
+            }
+            _ => return,
+        }
+        self.compile_filter();
+        // a search only highlights, the rows stay where they were
+        if !matches!(self.state.filter_mode, FilterMode::Search(_)) {
+            self.state.selected = 0;
+        }
+    }
+
+    // a filter that doesn't compile leaves the last good one in place
+    fn compile_filter(&mut self) {
+        match self.state.filter_mode.compile() {
+            Ok(expr) => {
+                self.state.filter_expr = expr;
+                self.state.filter_error = None;
+            }
+            Err(err) => self.state.filter_error = Some(format!("{:#}", err)),
+        }
+    }
+
+    // picking the column the table is already sorted by flips the direction instead
+    fn set_sort(&mut self, order: SortOrder) {
+        if self.state.sort_state.order == order {
+            self.state.sort_state.direction = self.state.sort_state.direction.reversed();
+        } else {
+            self.state.sort_state = SortState::new(order);
+        }
+        self.state.selected = 0;
+    }
+
+    async fn handle_io_priority_key(&mut self, code: KeyCode) {
+        let Some(picker) = self.state.io_priority_picker.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.cursor = picker.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                picker.cursor = (picker.cursor + 1).min(picker.choices.len().saturating_sub(1));
+            }
+            KeyCode::Esc => self.state.io_priority_picker = None,
+            KeyCode::Enter => {
+                if let Some(picker) = self.state.io_priority_picker.take() {
+                    self.set_io_priority(picker.pid, picker.choices[picker.cursor]).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Changes the I/O priority of `pid` and reports the outcome in the status line.
+    pub async fn set_io_priority(&mut self, pid: u32, priority: IoPriority) {
+        let result = self.system_monitor.read().await.set_io_priority(pid, priority);
+        self.state.status_message = Some(match result {
+            Ok(()) => format!("I/O priority of PID {} set to {}", pid, priority),
+            Err(err) => format!("{:#}", err),
+        });
+    }
+
+    async fn handle_input_key(&mut self, code: KeyCode) {
+        let Some(input) = self.state.input.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) => input.buffer.push(c),
+            KeyCode::Backspace => {
+                input.buffer.pop();
+            }
+            KeyCode::Esc => self.state.input = None,
+            KeyCode::Enter => {
+                if let Some(input) = self.state.input.take() {
+                    self.submit_input(input).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn submit_input(&mut self, input: InputPrompt) {
+        match input.kind {
+            InputKind::Command => self.execute_command(&input.buffer).await,
+            InputKind::Find => {
+                if !self.find_and_select(&input.buffer).await {
+                    self.state.status_message = Some(format!("No process starting with '{}'", input.buffer));
+                }
+            }
+            InputKind::OomScoreAdj(pid) => {
+                let result = match input.buffer.trim().parse::<i32>() {
+                    Ok(value) => self.system_monitor.read().await.set_oom_score_adj(pid, value),
+                    Err(_) => Err(anyhow::anyhow!("'{}' is not a number", input.buffer.trim())),
+                };
+                self.state.status_message = Some(match result {
+                    Ok(()) => format!("oom_score_adj updated for PID {}", pid),
+                    Err(err) => format!("{:#}", err),
+                });
+            }
+            InputKind::Nice(pid) => {
+                let result = match input.buffer.trim().parse::<i32>() {
+                    Ok(value) => self.system_monitor.read().await.set_nice(pid, value),
+                    Err(_) => Err(anyhow::anyhow!("'{}' is not a number between -20 and 19", input.buffer.trim())),
+                };
+                self.state.status_message = Some(match result {
+                    Ok(()) => format!("Nice value of PID {} set to {}", pid, input.buffer.trim()),
+                    Err(err) => format!("{:#}", err),
+                });
+                self.status_clears_on_refresh = true;
+            }
+            InputKind::Swappiness => {
+                let result = match input.buffer.trim().parse::<u32>() {
+                    Ok(value) => self.system_monitor.write().await.set_swappiness(value),
+                    Err(_) => Err(anyhow::anyhow!("'{}' is not a number between 0 and {}", input.buffer.trim(), MAX_SWAPPINESS)),
+                };
+                self.state.status_message = Some(match result {
+                    Ok(()) => format!("Swappiness set to {}", input.buffer.trim()),
+                    Err(err) => format!("{:#}", err),
+                });
+            }
+        }
+    }
+
+    async fn execute_command(&mut self, command: &str) {
+        let mut parts = command.split_whitespace();
+        match parts.next() {
+            Some("reset-history") => {
+                self.system_monitor.write().await.reset_history();
+                self.state.status_message = Some("History cleared".to_string());
+            }
+            Some("select") => match parts.next().and_then(|pid| pid.parse::<u32>().ok()) {
+                Some(pid) if self.select_process_by_pid(pid).await => {}
+                Some(pid) => self.state.status_message = Some(format!("PID {} is not in the current list", pid)),
+                None => self.state.status_message = Some("Usage: select <pid>".to_string()),
+            },
+            Some("hide") => match parts.next().and_then(|pid| pid.parse::<u32>().ok()) {
+                Some(pid) => self.hide_process(pid),
+                None => self.state.status_message = Some("Usage: hide <pid>".to_string()),
+            },
+            Some("export-keys") => match parts.next() {
+                Some(path) => {
+                    self.state.status_message = Some(match self.export_keybindings(Path::new(path)) {
+                        Ok(()) => format!("Keybindings written to {}", path),
+                        Err(err) => format!("{:#}", err),
+                    });
+                }
+                None => self.state.status_message = Some("Usage: export-keys <path>".to_string()),
+            },
+            Some("q") | Some("quit") => self.should_quit = true,
+            Some(other) => self.state.status_message = Some(format!("Unknown command: {}", other)),
+            None => {}
+        }
+    }
+
+    fn check_terminal_size<B: Backend>(&mut self, terminal: &Terminal<B>) -> Result<()> {
+        let size = terminal.size()?;
+        if size.width >= ui::MIN_TERMINAL_WIDTH && size.height >= ui::MIN_TERMINAL_HEIGHT {
+            self.too_small_since = None;
+            return Ok(());
+        }
+
+        let since = *self.too_small_since.get_or_insert_with(Instant::now);
+        if since.elapsed() >= self.resize_timeout {
+            bail!(
+                "Terminal too small ({}x{}), need at least {}x{}",
+                size.width,
+                size.height,
+                ui::MIN_TERMINAL_WIDTH,
+                ui::MIN_TERMINAL_HEIGHT
+            );
+        }
+        Ok(())
+    }
+
     async fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         let monitor = self.system_monitor.read().await;
+        let (depths, processes): (Vec<usize>, Vec<ProcessInfo>) = self.visible_rows(&monitor).into_iter().unzip();
+        if self.state.top_n.is_some() {
+            // the limit can leave the selection past the end once the list is cut
+            self.state.selected = self.state.selected.min(processes.len().saturating_sub(1));
+        }
         
+        // the frame closure can't return an error itself, carry it out
+        let mut result = Ok(());
         terminal.draw(|f| {
-            ui::draw_ui(
-                f,
-                &monitor,
-                self.selected_process,
-                &self.sort_order,
-                self.debug_mode,
-            );
+            result = ui::draw_ui(f, &monitor, &processes, &depths, &mut self.state);
         })?;
-        
-        Ok(())
+
+        result
+    }
+
+    /// Moves the selection to `pid` if it is in the current (filtered and sorted) list.
+    pub async fn select_process_by_pid(&mut self, pid: u32) -> bool {
+        let monitor = self.system_monitor.read().await;
+        let Some(index) = self.visible_processes(&monitor).iter().position(|p| p.pid == pid) else {
+            return false;
+        };
+        self.state.selected = index;
+        // bring the row to the top, draw_ui pulls the offset back if that leaves the table half empty
+        self.state.scroll_offset = index;
+        true
+    }
+
+    /// Selects the first process, in the current sort order, whose name starts with `query`.
+    pub async fn find_and_select(&mut self, query: &str) -> bool {
+        let pid = {
+            let monitor = self.system_monitor.read().await;
+            self.visible_processes(&monitor)
+                .iter()
+                .find(|p| p.name.starts_with(query))
+                .map(|p| p.pid)
+        };
+        match pid {
+            Some(pid) => self.select_process_by_pid(pid).await,
+            None => false,
+        }
     }
 
     async fn move_selection_up(&mut self) {
-        if self.selected_process > 0 {
-            self.selected_process -= 1;
+        if self.state.selected > 0 {
+            self.state.selected -= 1;
         }
     }
 
-    async fn move_selection_down(&mut self) {
+    pub fn cycle_layout(&mut self) {
+        self.state.layout = self.state.layout.next();
+        self.state.status_message = Some(format!("Layout: {}", self.state.layout.name()));
+    }
+
+    async fn open_detail(&mut self) {
+        if self.state.ports_tab.is_some() || self.state.container_view {
+            return;
+        }
         let monitor = self.system_monitor.read().await;
-        let processes = monitor.get_processes(&self.sort_order);
-        if self.selected_process < processes.len().saturating_sub(1) {
-            self.selected_process += 1;
+        let processes = self.visible_processes(&monitor);
+        if let Some(process) = processes.get(self.state.selected) {
+            self.state.mode = AppMode::DetailView(process.pid);
         }
     }
 
-    async fn kill_selected_process(&mut self) -> Result<()> {
+    async fn update_follow(&mut self) {
+        if self.state.follow.as_ref().is_none_or(|follow| follow.root_exited) {
+            return;
+        }
         let monitor = self.system_monitor.read().await;
-        let processes = monitor.get_processes(&self.sort_order);
-        
-        if let Some(process) = processes.get(self.selected_process) {
-            // attempt to kill the process (requires appropriate permissions)
-            #[cfg(unix)]
-            {
-                use std::process::Command;
-                let _ = Command::new("kill")
-                    .arg("-9")
-                    .arg(process.pid.to_string())
-                    .output();
-            }
-            
-            #[cfg(windows)]
-            {
-                use std::process::Command;
-                let _ = Command::new("taskkill")
-                    .args(&["/F", "/PID", &process.pid.to_string()])
-                    .output();
+        let processes = monitor.get_processes(&SortOrder::Pid);
+        if let Some(follow) = self.state.follow.as_mut() {
+            follow.update(&processes);
+        }
+    }
+
+    // sorted processes with the startup filters applied
+    fn filtered_processes(&self, monitor: &SystemMonitor) -> Vec<ProcessInfo> {
+        let filter = self.state.filter_expr.as_ref().filter(|_| self.state.filter_mode.hides());
+        let name_matches = |p: &ProcessInfo| filter.is_none_or(|filter| filter.matches(&p.name));
+        let mut processes = if self.state.swap_view {
+            monitor.get_swap_processes()
+        } else if self.state.priority_view {
+            monitor.get_high_priority_processes()
+        } else if let Some(filter) = filter {
+            monitor.get_processes_filtered(&self.state.sort_state, filter)
+        } else {
+            monitor.get_sorted_processes(&self.state.sort_state)
+        };
+        // the swap and priority views don't take a filter themselves
+        if self.state.swap_view || self.state.priority_view {
+            processes.retain(name_matches);
+        }
+        if let Some(pid) = self.watch_pid {
+            processes.retain(|p| p.pid == pid);
+        }
+        if !self.excluded_pids.is_empty() {
+            processes.retain(|p| !self.excluded_pids.contains(&p.pid));
+        }
+        if !self.excluded_users.is_empty() {
+            // entries are names or numeric uids
+            processes.retain(|p| {
+                !self
+                    .excluded_users
+                    .iter()
+                    .any(|user| *user == p.user || p.uid.is_some_and(|uid| user.parse() == Ok(uid)))
+            });
+        }
+        if let Some(prefix) = &self.cgroup_filter {
+            processes.retain(|p| {
+                monitor.get_process_cgroup(p.pid).is_some_and(|cgroup| {
+                    prefix == "/"
+                        || cgroup == *prefix
+                        || cgroup.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with('/'))
+                })
+            });
+        }
+        if let Some(baseline) = self.state.baseline_snapshot.as_ref().filter(|_| self.state.diff_mode) {
+            // the baseline has no kernel threads to compare against
+            processes = baseline.diff(&processes);
+        } else if self.show_kernel_threads && !self.state.swap_view && !self.state.priority_view {
+            // kernel threads go after user processes as their own section
+            processes.extend(monitor.get_kernel_threads().into_iter().filter(name_matches));
+        }
+        if let Some(n) = self.state.top_n {
+            processes.truncate(n);
+        }
+        processes
+    }
+
+    // the rows currently shown in the table, in display order with their depth in the tree
+    // (0 everywhere outside the tree views)
+    fn visible_rows(&self, monitor: &SystemMonitor) -> Vec<(usize, ProcessInfo)> {
+        let processes = self.filtered_processes(monitor);
+        match &self.state.follow {
+            Some(follow) => follow.tree(&processes),
+            // the other views ignore tree mode
+            None if self.state.tree_mode && !self.state.swap_view && !self.state.priority_view && !self.state.diff_mode => {
+                build_process_tree(&processes)
             }
+            None => processes.into_iter().map(|p| (0, p)).collect(),
         }
-        
-        Ok(())
     }
-}
\ No newline at end of file
+
+    fn visible_processes(&self, monitor: &SystemMonitor) -> Vec<ProcessInfo> {
+        self.visible_rows(monitor).into_iter().map(|(_, p)| p).collect()
+    }
+
+    async fn move_selection_down(&mut self) {
+        let monitor = self.system_monitor.read().await;
+        let processes = self.visible_processes(&monitor);
+        if self.state.selected < processes.len().saturating_sub(1) {
+            self.state.selected += 1;
+        }
+    }
+
+    async fn move_selection_page_up(&mut self) {
+        self.state.selected = page_up(self.state.selected, self.state.page_size);
+    }
+
+    async fn move_selection_page_down(&mut self) {
+        let monitor = self.system_monitor.read().await;
+        let count = self.visible_processes(&monitor).len();
+        self.state.selected = page_down(self.state.selected, self.state.page_size, count);
+    }
+
+    async fn move_selection_home(&mut self) {
+        self.state.selected = 0;
+    }
+
+    async fn move_selection_end(&mut self) {
+        let monitor = self.system_monitor.read().await;
+        self.state.selected = self.visible_processes(&monitor).len().saturating_sub(1);
+    }
+
+    // P cycles: process table -> TCP/UDP ports -> unix sockets -> process table
+    async fn toggle_ports_view(&mut self) {
+        self.state.ports_tab = match self.state.ports_tab {
+            None => Some(PortsTab::Inet),
+            Some(PortsTab::Inet) => Some(PortsTab::Unix),
+            Some(PortsTab::Unix) => None,
+        };
+        let mut monitor = self.system_monitor.write().await;
+        monitor.set_collect_ports(self.state.ports_tab.is_some());
+    }
+
+    fn hide_process(&mut self, pid: u32) {
+        self.excluded_pids.insert(pid);
+        self.state.status_message = Some(format!("PID {} hidden", pid));
+    }
+
+    async fn hide_selected_process(&mut self) {
+        let pid = {
+            let monitor = self.system_monitor.read().await;
+            self.visible_processes(&monitor).get(self.state.selected).map(|p| p.pid)
+        };
+        if let Some(pid) = pid {
+            self.hide_process(pid);
+            // the row below moves up into the selection, unless the hidden one was the last
+            let remaining = self.visible_processes(&*self.system_monitor.read().await).len();
+            self.state.selected = self.state.selected.min(remaining.saturating_sub(1));
+        }
+    }
+
+    async fn open_kill_dialog(&mut self) {
+        let monitor = self.system_monitor.read().await;
+        if let Some(process) = self.visible_processes(&monitor).get(self.state.selected) {
+            self.state.mode = AppMode::KillDialog(KillDialog::new(process.pid, process.name.clone()));
+        }
+    }
+
+    async fn send_signal(&mut self, pid: u32, name: &str, signal: Signal) {
+        let result = self.system_monitor.read().await.kill_process(pid, signal);
+        self.state.status_message = Some(match result {
+            Ok(()) => format!("Sent {} to {} (PID {})", signal, name, pid),
+            Err(err) => format!("{:#}", err),
+        });
+    }
+
+    async fn toggle_mark_selected(&mut self) {
+        let pid = {
+            let monitor = self.system_monitor.read().await;
+            self.visible_processes(&monitor).get(self.state.selected).map(|p| p.pid)
+        };
+        if let Some(pid) = pid {
+            self.state.toggle_mark(pid);
+        }
+    }
+
+    async fn confirm_batch_kill(&mut self) {
+        self.state.confirm_batch_kill(&*self.system_monitor.read().await);
+    }
+
+    // runs the batch kill the user confirmed and reports the pids that survived it
+    async fn kill_marked(&mut self, targets: &[(u32, String)]) {
+        let monitor = self.system_monitor.read().await;
+        let failed: Vec<String> = targets
+            .iter()
+            .filter(|(pid, _)| monitor.kill_process(*pid, SIGKILL).is_err())
+            .map(|(pid, _)| pid.to_string())
+            .collect();
+        drop(monitor);
+        self.state.selected_pids.clear();
+        self.state.status_message = Some(if failed.is_empty() {
+            format!("Killed {} processes", targets.len())
+        } else {
+            format!(
+                "Killed {} of {}, failed: {}",
+                targets.len() - failed.len(),
+                targets.len(),
+                failed.join(", ")
+            )
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! The columns of the process table, shared by the table drawing and the `[table]` section of
+//! the config. How each column is drawn and sorted lives in `ui`.
+
+/// A column of the process table. The table shows them in `AppState::column_order`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessColumn {
+    User,
+    Pid,
+    Name,
+    Cpu,
+    Memory,
+    MemoryPercent,
+    Threads,
+    Nice,
+    DiskRead,
+    DiskWrite,
+    Started,
+    Gpu,
+}
+
+impl ProcessColumn {
+    pub const ALL: [ProcessColumn; 12] = [
+        ProcessColumn::User,
+        ProcessColumn::Pid,
+        ProcessColumn::Name,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::MemoryPercent,
+        ProcessColumn::Threads,
+        ProcessColumn::Nice,
+        ProcessColumn::DiskRead,
+        ProcessColumn::DiskWrite,
+        ProcessColumn::Started,
+        ProcessColumn::Gpu,
+    ];
+
+    /// Name in the `[table]` section of the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProcessColumn::User => "user",
+            ProcessColumn::Pid => "pid",
+            ProcessColumn::Name => "name",
+            ProcessColumn::Cpu => "cpu",
+            ProcessColumn::Memory => "memory",
+            ProcessColumn::MemoryPercent => "memory-percent",
+            ProcessColumn::Threads => "threads",
+            ProcessColumn::Nice => "nice",
+            ProcessColumn::DiskRead => "disk-read",
+            ProcessColumn::DiskWrite => "disk-write",
+            ProcessColumn::Started => "started",
+            ProcessColumn::Gpu => "gpu",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ProcessColumn> {
+        ProcessColumn::ALL.into_iter().find(|column| column.name() == name)
+    }
+
+    /// Width before any resizing. The name column takes whatever the others leave, this is
+    /// its minimum.
+    pub fn default_width(self) -> u16 {
+        match self {
+            ProcessColumn::User => 10,
+            ProcessColumn::Pid => 8,
+            ProcessColumn::Name => 20,
+            ProcessColumn::Cpu => 8,
+            ProcessColumn::Memory => 10,
+            ProcessColumn::MemoryPercent => 8,
+            ProcessColumn::Threads => 7,
+            ProcessColumn::Nice => 4,
+            ProcessColumn::DiskRead | ProcessColumn::DiskWrite => 10,
+            ProcessColumn::Started => 11,
+            ProcessColumn::Gpu => 6,
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! The user config file, `~/.config/systop/config.toml`.
+//!
+//! Every key in `[theme]` is optional and takes a color name (`"cyan"`, `"light-blue"`), an
+//! index (`"208"`) or a hex value (`"#ff8800"`):
+//!
+//! ```toml
+//! [theme]
+//! accent = "magenta"
+//! table_header = "#303030"
+//! ```
+//!
+//! `[keybindings]` is described in [`crate::keybindings`]. Both sections are reloaded when the
+//! file changes.
+//!
+//! `[health_score_weights]` sets how much each factor of the process health score counts,
+//! e.g. `threads = 0` to ignore thread counts. `[memory_pressure_weights]` does the same for
+//! the memory pressure level in the header (`memory`, `swap`, `major_faults`, `dirty`). Both
+//! are only read at startup.
+//!
+//! `[table]` holds the process table's column order, e.g. `columns = ["pid", "name", "cpu"]`,
+//! and widths, e.g. `widths = { user = 16 }`. Dragging a column header or the separator
+//! between two rewrites them; columns the order leaves out go after the listed ones.
+
+use anyhow::{anyhow, bail, Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ratatui::style::Color;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
+
+use crate::keybindings::Keybindings;
+use crate::system::{HealthScoreWeights, MemoryPressureWeights};
+use crate::column::ProcessColumn;
+
+/// Colors the UI draws with. The defaults are the original hard-coded ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub accent: Color,       // app title, detail labels, status messages
+    pub border: Color,       // header border
+    pub table_header: Color, // background of table header rows
+    pub selected: Color,     // background of the selected row
+    pub sort_column: Color,  // header of the column the table is sorted by
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Cyan,
+            border: Color::Blue,
+            table_header: Color::Blue,
+            selected: Color::DarkGray,
+            sort_column: Color::Yellow,
+        }
+    }
+}
+
+impl Theme {
+    /// Parses a `[theme]` table, colors it leaves out keep their defaults.
+    pub fn from_table(table: &toml::Table) -> Result<Self> {
+        let mut theme = Theme::default();
+        for (key, value) in table {
+            let slot = match key.as_str() {
+                "accent" => &mut theme.accent,
+                "border" => &mut theme.border,
+                "table_header" => &mut theme.table_header,
+                "selected" => &mut theme.selected,
+                "sort_column" => &mut theme.sort_column,
+                other => bail!("unknown theme color '{}'", other),
+            };
+            let name = value
+                .as_str()
+                .with_context(|| format!("theme.{} must be a string", key))?;
+            *slot = name
+                .parse()
+                .map_err(|_| anyhow!("theme.{}: invalid color '{}'", key, name))?;
+        }
+        Ok(theme)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/systop/config.toml`, falling back to `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("systop").join("config.toml"))
+}
+
+fn read_config(path: &Path) -> Result<toml::Table> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read config {}", path.display()))?;
+    source
+        .parse()
+        .with_context(|| format!("failed to parse config {}", path.display()))
+}
+
+fn theme_section(config: &toml::Table) -> Result<Theme> {
+    match config.get("theme") {
+        None => Ok(Theme::default()),
+        Some(toml::Value::Table(table)) => Theme::from_table(table),
+        Some(_) => bail!("[theme] must be a table"),
+    }
+}
+
+fn keybindings_section(config: &toml::Table) -> (Keybindings, Vec<String>) {
+    match config.get("keybindings") {
+        None => (Keybindings::default(), Vec::new()),
+        Some(toml::Value::Table(table)) => Keybindings::from_table(table),
+        Some(_) => (Keybindings::default(), vec!["[keybindings] must be a table".to_string()]),
+    }
+}
+
+/// The `[theme]` section of the config at `path`, the default theme if there is none.
+pub fn load_theme(path: &Path) -> Result<Theme> {
+    theme_section(&read_config(path)?).with_context(|| format!("invalid theme in {}", path.display()))
+}
+
+/// The `[health_score_weights]` section of the config at `path`, weights it leaves out keep
+/// their defaults.
+pub fn load_health_score_weights(path: &Path) -> Result<HealthScoreWeights> {
+    let mut weights = HealthScoreWeights::default();
+    for (key, weight) in weights_section(&read_config(path)?, "health_score_weights")? {
+        let slot = match key.as_str() {
+            "cpu_spikes" => &mut weights.cpu_spikes,
+            "memory_growth" => &mut weights.memory_growth,
+            "major_faults" => &mut weights.major_faults,
+            "fd_usage" => &mut weights.fd_usage,
+            "threads" => &mut weights.threads,
+            other => bail!("unknown health score weight '{}'", other),
+        };
+        *slot = weight;
+    }
+    Ok(weights)
+}
+
+/// The `[memory_pressure_weights]` section of the config at `path`, weights it leaves out
+/// keep their defaults.
+pub fn load_memory_pressure_weights(path: &Path) -> Result<MemoryPressureWeights> {
+    let mut weights = MemoryPressureWeights::default();
+    for (key, weight) in weights_section(&read_config(path)?, "memory_pressure_weights")? {
+        let slot = match key.as_str() {
+            "memory" => &mut weights.memory,
+            "swap" => &mut weights.swap,
+            "major_faults" => &mut weights.major_faults,
+            "dirty" => &mut weights.dirty,
+            other => bail!("unknown memory pressure weight '{}'", other),
+        };
+        *slot = weight;
+    }
+    Ok(weights)
+}
+
+// every entry of the `[section]` table, empty if there is none
+fn weights_section(config: &toml::Table, section: &str) -> Result<Vec<(String, f32)>> {
+    let Some(table) = config.get(section) else {
+        return Ok(Vec::new());
+    };
+    let table = table
+        .as_table()
+        .with_context(|| format!("[{}] must be a table", section))?;
+    table
+        .iter()
+        .map(|(key, value)| {
+            // accept `2` as well as `2.0`
+            let weight = value
+                .as_float()
+                .or_else(|| value.as_integer().map(|weight| weight as f64))
+                .filter(|weight| *weight >= 0.0)
+                .with_context(|| format!("{}.{} must be a number of at least 0", section, key))?;
+            Ok((key.clone(), weight as f32))
+        })
+        .collect()
+}
+
+/// The column order from `[table] columns` in the config at `path`, None if it isn't set.
+pub fn load_column_order(path: &Path) -> Result<Option<Vec<ProcessColumn>>> {
+    let config = read_config(path)?;
+    let Some(columns) = config.get("table").and_then(|table| table.get("columns")) else {
+        return Ok(None);
+    };
+    let names = columns
+        .as_array()
+        .context("table.columns must be a list of column names")?;
+    let mut order = Vec::new();
+    for name in names {
+        let column = name
+            .as_str()
+            .and_then(ProcessColumn::from_name)
+            .with_context(|| format!("table.columns: unknown column {}", name))?;
+        if !order.contains(&column) {
+            order.push(column);
+        }
+    }
+    let missing: Vec<ProcessColumn> = ProcessColumn::ALL.into_iter().filter(|column| !order.contains(column)).collect();
+    order.extend(missing);
+    Ok(Some(order))
+}
+
+/// Column widths set in `[table] widths` of the config at `path`.
+pub fn load_column_widths(path: &Path) -> Result<Vec<(ProcessColumn, u16)>> {
+    let config = read_config(path)?;
+    let Some(widths) = config.get("table").and_then(|table| table.get("widths")) else {
+        return Ok(Vec::new());
+    };
+    let widths = widths
+        .as_table()
+        .context("table.widths must be a table of column = width")?;
+    widths
+        .iter()
+        .map(|(name, width)| {
+            let column = ProcessColumn::from_name(name).with_context(|| format!("table.widths: unknown column {}", name))?;
+            let width = width
+                .as_integer()
+                .and_then(|width| u16::try_from(width).ok())
+                .with_context(|| format!("table.widths.{} must be a width in cells", name))?;
+            Ok((column, width))
+        })
+        .collect()
+}
+
+/// Writes `order` to `[table] columns` in the config at `path`, creating the file if needed.
+/// The rest of the file, comments included, is left as it is.
+pub fn save_column_order(path: &Path, order: &[ProcessColumn]) -> Result<()> {
+    let columns: toml_edit::Array = order.iter().map(|column| column.name()).collect();
+    update_table_section(path, "columns", toml_edit::value(columns))
+}
+
+/// Writes `widths` to `[table] widths` in the config at `path`, like `save_column_order`.
+pub fn save_column_widths(path: &Path, widths: &[(ProcessColumn, u16)]) -> Result<()> {
+    let mut table = toml_edit::InlineTable::new();
+    for (column, width) in widths {
+        table.insert(column.name(), i64::from(*width).into());
+    }
+    update_table_section(path, "widths", toml_edit::value(table))
+}
+
+fn update_table_section(path: &Path, key: &str, value: toml_edit::Item) -> Result<()> {
+    let mut doc = match std::fs::read_to_string(path) {
+        Ok(source) => source
+            .parse::<toml_edit::DocumentMut>()
+            .with_context(|| format!("failed to parse config {}", path.display()))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => toml_edit::DocumentMut::new(),
+        Err(err) => return Err(err).with_context(|| format!("failed to read config {}", path.display())),
+    };
+    let table = doc
+        .entry("table")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .context("[table] must be a table")?;
+    table.insert(key, value);
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, doc.to_string()).with_context(|| format!("failed to write config {}", path.display()))
+}
+
+/// The `[keybindings]` section of the config at `path`, plus a description of every entry
+/// that had to be skipped.
+pub fn load_keybindings(path: &Path) -> Result<(Keybindings, Vec<String>)> {
+    Ok(keybindings_section(&read_config(path)?))
+}
+
+/// Sends the re-parsed theme and keybindings whenever the config at `path` changes. A broken
+/// theme is logged and the current one kept; keybinding errors are sent along with the
+/// entries that did parse. Watching stops when the returned watcher is dropped.
+pub fn watch_config(
+    path: &Path,
+    theme_tx: watch::Sender<Theme>,
+    keys_tx: watch::Sender<(Keybindings, Vec<String>)>,
+) -> Result<RecommendedWatcher> {
+    let dir = path
+        .parent()
+        .with_context(|| format!("config path {} has no parent directory", path.display()))?;
+    let file = path.to_path_buf();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !(event.kind.is_modify() || event.kind.is_create()) || !event.paths.contains(&file) {
+            return;
+        }
+        let config = match read_config(&file) {
+            Ok(config) => config,
+            Err(err) => {
+                tracing::warn!("keeping the current config: {:#}", err);
+                return;
+            }
+        };
+        match theme_section(&config) {
+            Ok(theme) => send_if_changed(&theme_tx, theme),
+            Err(err) => tracing::warn!("keeping the current theme: {:#}", err),
+        }
+        send_if_changed(&keys_tx, keybindings_section(&config));
+    })
+    .context("failed to create config watcher")?;
+    // editors often save by writing a new file and renaming it over the old one, which a watch
+    // on the file itself would lose track of
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("failed to watch {}", dir.display()))?;
+    Ok(watcher)
+}
+
+// saving the file often fires several events, only wake the receiver for real changes
+fn send_if_changed<T: PartialEq>(tx: &watch::Sender<T>, value: T) {
+    tx.send_if_modified(|current| {
+        let changed = *current != value;
+        *current = value;
+        changed
+    });
+}
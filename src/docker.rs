@@ -0,0 +1,155 @@
+//! Running containers from the Docker Engine API. The daemon's unix socket speaks plain HTTP,
+//! a one-line HTTP/1.0 request gets the whole JSON body back without chunked encoding, so no
+//! HTTP client is needed.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+// per request; daemons older than API 1.41 ignore one-shot and take a second to sample cpu
+#[cfg_attr(not(unix), allow(dead_code))]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+// stats requests in flight at once, each one is a thread blocked on the socket
+#[cfg_attr(not(unix), allow(dead_code))]
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ContainerInfo {
+    pub id: String, // short form, the first 12 hex digits like `docker ps`
+    pub name: String,
+    pub status: String, // e.g. "Up 3 hours (healthy)"
+    pub cpu_percent: f32, // since the previous poll, 100 per fully used cpu
+    pub memory_bytes: u64, // page cache that can be reclaimed isn't counted, like `docker stats`
+}
+
+/// CPU counters of a container as of one poll, `cpu_percent` is the delta to the next.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuSample {
+    pub container_ns: u64,
+    pub system_ns: u64,
+}
+
+/// The daemon socket, DOCKER_HOST when it names a unix socket.
+pub fn socket_path() -> String {
+    std::env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| host.strip_prefix("unix://").map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_SOCKET.to_string())
+}
+
+/// Every running container, busiest first. `samples` holds the cpu counters of the previous
+/// call and is replaced with this call's; containers that stopped are dropped from it.
+/// Blocks on the socket, the stats of the containers are requested concurrently.
+#[cfg(unix)]
+pub fn list_containers(socket: &str, samples: &mut HashMap<String, CpuSample>) -> Result<Vec<ContainerInfo>> {
+    let summaries = get_json(socket, "/containers/json")?;
+    let summaries = summaries.as_array().context("unexpected /containers/json response")?;
+    let mut previous = std::mem::take(samples);
+    let mut containers = Vec::with_capacity(summaries.len());
+    for batch in summaries.chunks(MAX_CONCURRENT_REQUESTS) {
+        let stats: Vec<Option<Value>> = std::thread::scope(|scope| {
+            let requests: Vec<_> = batch
+                .iter()
+                .map(|summary| {
+                    scope.spawn(move || {
+                        let id = summary["Id"].as_str()?;
+                        // a container stopping between the two requests isn't an error
+                        get_json(socket, &format!("/containers/{}/stats?stream=false&one-shot=true", id)).ok()
+                    })
+                })
+                .collect();
+            requests.into_iter().map(|request| request.join().ok().flatten()).collect()
+        });
+        for (summary, stats) in batch.iter().zip(stats) {
+            let (Some(id), Some(stats)) = (summary["Id"].as_str(), stats) else {
+                continue;
+            };
+            let (container, sample) = container_info(summary, &stats, previous.remove(id));
+            samples.insert(id.to_string(), sample);
+            containers.push(container);
+        }
+    }
+    containers.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+    Ok(containers)
+}
+
+#[cfg(not(unix))]
+pub fn list_containers(_socket: &str, _samples: &mut HashMap<String, CpuSample>) -> Result<Vec<ContainerInfo>> {
+    bail!("the Docker socket is only supported on unix")
+}
+
+/// One container from its /containers/json entry and a /containers/{id}/stats response.
+/// The cpu usage needs the sample of the previous poll, without one it's 0.
+fn container_info(summary: &Value, stats: &Value, previous: Option<CpuSample>) -> (ContainerInfo, CpuSample) {
+    let cpu = &stats["cpu_stats"];
+    let sample = CpuSample {
+        container_ns: cpu["cpu_usage"]["total_usage"].as_u64().unwrap_or(0),
+        system_ns: cpu["system_cpu_usage"].as_u64().unwrap_or(0),
+    };
+    let online_cpus = cpu["online_cpus"]
+        .as_u64()
+        .or_else(|| cpu["cpu_usage"]["percpu_usage"].as_array().map(|cpus| cpus.len() as u64))
+        .unwrap_or(1);
+    let cpu_percent = match previous {
+        Some(previous) if sample.system_ns > previous.system_ns => {
+            let container = sample.container_ns.saturating_sub(previous.container_ns) as f64;
+            let system = (sample.system_ns - previous.system_ns) as f64;
+            (container / system * online_cpus as f64 * 100.0) as f32
+        }
+        _ => 0.0,
+    };
+
+    // inactive_file on cgroup v2, total_inactive_file on v1
+    let memory = &stats["memory_stats"];
+    let inactive_file = memory["stats"]["inactive_file"]
+        .as_u64()
+        .or_else(|| memory["stats"]["total_inactive_file"].as_u64())
+        .unwrap_or(0);
+    let memory_bytes = memory["usage"].as_u64().unwrap_or(0).saturating_sub(inactive_file);
+
+    let id = summary["Id"].as_str().unwrap_or_default();
+    let container = ContainerInfo {
+        id: id.chars().take(12).collect(),
+        name: summary["Names"][0].as_str().unwrap_or_default().trim_start_matches('/').to_string(),
+        status: summary["Status"].as_str().unwrap_or_default().to_string(),
+        cpu_percent,
+        memory_bytes,
+    };
+    (container, sample)
+}
+
+#[cfg(unix)]
+fn get_json(socket: &str, path: &str) -> Result<Value> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket).with_context(|| format!("failed to connect to {}", socket))?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    // HTTP/1.0: the daemon closes the connection after the response, read_to_string ends there
+    stream.write_all(format!("GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).as_bytes())?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .with_context(|| format!("failed to read {} from {}", path, socket))?;
+    parse_response(&response)
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_response(response: &str) -> Result<Value> {
+    let (head, body) = response.split_once("\r\n\r\n").context("malformed HTTP response from Docker")?;
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        // errors come as {"message": "..."}
+        let message = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|error| error["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| body.trim().to_string());
+        bail!("Docker API returned {}: {}", status, message);
+    }
+    serde_json::from_str(body).context("invalid JSON from the Docker API")
+}
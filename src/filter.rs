@@ -0,0 +1,92 @@
+//! Process name filters typed in the footer. In search and exclude mode `/pattern/` is a regex,
+//! anything else a plain substring; in regex mode the whole text is the pattern. All of them
+//! ignore case.
+
+use anyhow::{anyhow, Result};
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone)]
+pub enum FilterExpr {
+    Substring(String), // stored lowercased
+    Regex(Regex),
+}
+
+impl FilterExpr {
+    pub fn parse(text: &str) -> Result<FilterExpr> {
+        let pattern = text
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'));
+        match pattern {
+            Some(pattern) => FilterExpr::regex(pattern),
+            None => Ok(FilterExpr::Substring(text.to_lowercase())),
+        }
+    }
+
+    pub fn regex(pattern: &str) -> Result<FilterExpr> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(FilterExpr::Regex)
+            // syntax errors quote the pattern over several lines, the last one says what's wrong
+            .map_err(|err| {
+                let message = err.to_string();
+                let reason = message.lines().last().unwrap_or_default().trim_start_matches("error: ");
+                anyhow!("invalid regex /{}/: {}", pattern, reason)
+            })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            FilterExpr::Substring(needle) => name.to_lowercase().contains(needle.as_str()),
+            FilterExpr::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// What the filter typed in the footer does to the process list, with the text typed so far.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterMode {
+    None,
+    Search(String),  // highlight matching processes, list them all
+    Exclude(String), // hide the processes that don't match
+    Regex(String),   // like Exclude, the text being a regex without the slashes
+}
+
+impl FilterMode {
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            FilterMode::None => None,
+            FilterMode::Search(text) | FilterMode::Exclude(text) | FilterMode::Regex(text) => Some(text),
+        }
+    }
+
+    pub fn text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            FilterMode::None => None,
+            FilterMode::Search(text) | FilterMode::Exclude(text) | FilterMode::Regex(text) => Some(text),
+        }
+    }
+
+    /// Prompt shown in front of the text.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterMode::None => "",
+            FilterMode::Search(_) => "Search",
+            FilterMode::Exclude(_) => "Exclude",
+            FilterMode::Regex(_) => "Regex",
+        }
+    }
+
+    /// Whether processes that don't match are left out of the list.
+    pub fn hides(&self) -> bool {
+        matches!(self, FilterMode::Exclude(_) | FilterMode::Regex(_))
+    }
+
+    /// None while there is no text to match against.
+    pub fn compile(&self) -> Result<Option<FilterExpr>> {
+        match self {
+            FilterMode::Regex(text) if !text.is_empty() => FilterExpr::regex(text).map(Some),
+            mode => mode.text().filter(|text| !text.is_empty()).map(FilterExpr::parse).transpose(),
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! Key to action mapping for the process list, configurable in the `[keybindings]` section of
+//! the config file. Each entry replaces the default keys of one action:
+//!
+//! ```toml
+//! [keybindings]
+//! quit = "x"
+//! sort-cpu = ["c", "ctrl-p"]
+//! ```
+//!
+//! Prompts, the affinity editor and the detail view keep their fixed keys.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    SelectFirst,
+    SelectLast,
+    ToggleKernelThreads,
+    KillProcess,
+    ToggleMark,
+    Renice,
+    SortCpu,
+    SortMemory,
+    SortOomScore,
+    SortMajorFaults,
+    SortMemoryEfficiency,
+    SortHealthScore,
+    SortThreads,
+    SortDiskRead,
+    SortDiskWrite,
+    SortUser,
+    SortStartTime,
+    SortNice,
+    SortGpu,
+    ToggleTreeView,
+    ToggleSwapView,
+    TogglePriorityView,
+    OpenDetail,
+    TogglePorts,
+    CycleLayout,
+    ShowLegend,
+    ResetHistory,
+    EditSwappiness,
+    CommandPalette,
+    FindProcess,
+    SearchProcesses,
+    FilterProcesses,
+    RegexFilter,
+    HideProcess,
+    ToggleDiffMode,
+    TogglePause,
+    ToggleAutoScroll,
+    CycleInterface,
+    CycleView,
+    ToggleContainers,
+}
+
+impl Action {
+    pub const ALL: [Action; 45] = [
+        Action::Quit,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::SelectFirst,
+        Action::SelectLast,
+        Action::ToggleKernelThreads,
+        Action::KillProcess,
+        Action::ToggleMark,
+        Action::Renice,
+        Action::SortCpu,
+        Action::SortMemory,
+        Action::SortOomScore,
+        Action::SortMajorFaults,
+        Action::SortMemoryEfficiency,
+        Action::SortHealthScore,
+        Action::SortThreads,
+        Action::SortDiskRead,
+        Action::SortDiskWrite,
+        Action::SortUser,
+        Action::SortStartTime,
+        Action::SortNice,
+        Action::SortGpu,
+        Action::ToggleTreeView,
+        Action::ToggleSwapView,
+        Action::TogglePriorityView,
+        Action::OpenDetail,
+        Action::TogglePorts,
+        Action::CycleLayout,
+        Action::ShowLegend,
+        Action::ResetHistory,
+        Action::EditSwappiness,
+        Action::CommandPalette,
+        Action::FindProcess,
+        Action::SearchProcesses,
+        Action::FilterProcesses,
+        Action::RegexFilter,
+        Action::HideProcess,
+        Action::ToggleDiffMode,
+        Action::TogglePause,
+        Action::ToggleAutoScroll,
+        Action::CycleInterface,
+        Action::CycleView,
+        Action::ToggleContainers,
+    ];
+
+    /// The name used in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::MoveUp => "move-up",
+            Action::MoveDown => "move-down",
+            Action::PageUp => "page-up",
+            Action::PageDown => "page-down",
+            Action::SelectFirst => "select-first",
+            Action::SelectLast => "select-last",
+            Action::ToggleKernelThreads => "toggle-kernel-threads",
+            Action::KillProcess => "kill-process",
+            Action::ToggleMark => "toggle-mark",
+            Action::Renice => "renice",
+            Action::SortCpu => "sort-cpu",
+            Action::SortMemory => "sort-memory",
+            Action::SortOomScore => "sort-oom-score",
+            Action::SortMajorFaults => "sort-major-faults",
+            Action::SortMemoryEfficiency => "sort-memory-efficiency",
+            Action::SortHealthScore => "sort-health-score",
+            Action::SortThreads => "sort-threads",
+            Action::SortDiskRead => "sort-disk-read",
+            Action::SortDiskWrite => "sort-disk-write",
+            Action::SortUser => "sort-user",
+            Action::SortStartTime => "sort-start-time",
+            Action::SortNice => "sort-nice",
+            Action::SortGpu => "sort-gpu",
+            Action::ToggleTreeView => "toggle-tree-view",
+            Action::ToggleSwapView => "toggle-swap-view",
+            Action::TogglePriorityView => "toggle-priority-view",
+            Action::OpenDetail => "open-detail",
+            Action::TogglePorts => "toggle-ports",
+            Action::CycleLayout => "cycle-layout",
+            Action::ShowLegend => "show-legend",
+            Action::ResetHistory => "reset-history",
+            Action::EditSwappiness => "edit-swappiness",
+            Action::CommandPalette => "command-palette",
+            Action::FindProcess => "find-process",
+            Action::SearchProcesses => "search-processes",
+            Action::FilterProcesses => "filter-processes",
+            Action::RegexFilter => "regex-filter",
+            Action::HideProcess => "hide-process",
+            Action::ToggleDiffMode => "toggle-diff-mode",
+            Action::TogglePause => "toggle-pause",
+            Action::ToggleAutoScroll => "toggle-auto-scroll",
+            Action::CycleInterface => "cycle-interface",
+            Action::CycleView => "cycle-view",
+            Action::ToggleContainers => "toggle-containers",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit systop",
+            Action::MoveUp => "Select the previous process",
+            Action::MoveDown => "Select the next process",
+            Action::PageUp => "Move the selection up one screen",
+            Action::PageDown => "Move the selection down one screen",
+            Action::SelectFirst => "Select the first process",
+            Action::SelectLast => "Select the last process",
+            Action::ToggleKernelThreads => "Show or hide kernel threads",
+            Action::KillProcess => "Pick a signal to send the selected process, or kill every marked one after confirming",
+            Action::ToggleMark => "Mark or unmark the selected process for a batch kill",
+            Action::Renice => "Change the selected process's nice value (-20..19)",
+            Action::SortCpu => "Sort by CPU usage",
+            Action::SortMemory => "Sort by memory usage",
+            Action::SortOomScore => "Sort by OOM score",
+            Action::SortMajorFaults => "Sort by major page faults",
+            Action::SortMemoryEfficiency => "Sort by resident/virtual memory ratio, lowest first",
+            Action::SortHealthScore => "Sort by health score, least healthy first",
+            Action::SortThreads => "Sort by thread count",
+            Action::SortDiskRead => "Sort by disk read rate",
+            Action::SortDiskWrite => "Sort by disk write rate",
+            Action::SortUser => "Sort by owning user",
+            Action::SortStartTime => "Sort by start time, oldest first",
+            Action::SortNice => "Sort by nice value, highest priority first",
+            Action::SortGpu => "Sort by GPU usage (needs the gpu feature)",
+            Action::ToggleTreeView => "Nest processes under their parent",
+            Action::ToggleSwapView => "Switch between all processes and swap users",
+            Action::TogglePriorityView => "Switch between all processes and those with a negative nice value",
+            Action::OpenDetail => "Open the detail view of the selected process",
+            Action::TogglePorts => "Show or hide listening ports and unix sockets",
+            Action::CycleLayout => "Switch to the next layout preset",
+            Action::ShowLegend => "Explain the colors used in the UI",
+            Action::ResetHistory => "Clear CPU and memory history (debug mode only)",
+            Action::EditSwappiness => "Change vm.swappiness (requires root)",
+            Action::CommandPalette => "Open the command prompt",
+            Action::FindProcess => "Jump to a process by name",
+            Action::SearchProcesses => "Highlight processes whose name contains a string",
+            Action::FilterProcesses => "Only list processes whose name contains a string",
+            Action::RegexFilter => "Only list processes whose name matches a regex",
+            Action::HideProcess => "Hide the selected process until systop exits",
+            Action::ToggleDiffMode => "Show only what changed since pressing it",
+            Action::TogglePause => "Stop or resume refreshing the system data",
+            Action::ToggleAutoScroll => "Keep the top CPU or memory user selected after every refresh",
+            Action::CycleInterface => "Open the network panel and step through its interfaces, past the last one closes it",
+            Action::CycleView => "Cycle what's above the process table: stats, network, disks, nothing",
+            Action::ToggleContainers => "Show running Docker containers instead of processes",
+        }
+    }
+
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "ctrl-c"],
+            Action::MoveUp => &["up", "k"],
+            Action::MoveDown => &["down", "j"],
+            Action::PageUp => &["pageup"],
+            Action::PageDown => &["pagedown"],
+            Action::SelectFirst => &["home"],
+            Action::SelectLast => &["end"],
+            Action::ToggleKernelThreads => &["ctrl-k"],
+            Action::KillProcess => &["K"],
+            Action::ToggleMark => &["v"],
+            Action::Renice => &["N"],
+            Action::SortCpu => &["c"],
+            Action::SortMemory => &["m"],
+            Action::SortOomScore => &["o"],
+            Action::SortMajorFaults => &["M"],
+            Action::SortMemoryEfficiency => &["e"],
+            Action::SortHealthScore => &["h"],
+            Action::SortThreads => &["t"],
+            Action::SortDiskRead => &["r"],
+            Action::SortDiskWrite => &["w"],
+            Action::SortUser => &["u"],
+            Action::SortStartTime => &["s"],
+            Action::SortNice => &["n"],
+            Action::SortGpu => &["g"],
+            Action::ToggleTreeView => &["T"],
+            Action::ToggleSwapView => &["X"],
+            Action::TogglePriorityView => &["p"],
+            Action::OpenDetail => &["enter"],
+            Action::TogglePorts => &["P"],
+            Action::CycleLayout => &["L"],
+            Action::ShowLegend => &["l"],
+            Action::ResetHistory => &["R"],
+            Action::EditSwappiness => &["S"],
+            Action::CommandPalette => &[":"],
+            Action::FindProcess => &["f"],
+            Action::SearchProcesses => &["/"],
+            Action::FilterProcesses => &["!"],
+            Action::RegexFilter => &["`"],
+            Action::HideProcess => &["H"],
+            Action::ToggleDiffMode => &["D"],
+            Action::TogglePause => &["space"],
+            Action::ToggleAutoScroll => &["f2"],
+            Action::CycleInterface => &["i"],
+            Action::CycleView => &["tab"],
+            Action::ToggleContainers => &["C"],
+        }
+    }
+}
+
+/// A key plus modifiers. Shift is folded into the character, so `K` rather than `shift-k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parses `"q"`, `"K"`, `"ctrl-k"`, `"alt-enter"`, `"f5"`, ...
+    pub fn parse(text: &str) -> Option<KeyBinding> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // a lone "-" is the minus key, not an empty modifier
+        while let Some((prefix, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return None,
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                name => KeyCode::F(name.strip_prefix('f')?.parse().ok().filter(|n| (1..=12).contains(n))?),
+            },
+        };
+        Some(KeyBinding { code, modifiers })
+    }
+
+    fn from_event(key: &KeyEvent) -> KeyBinding {
+        let mut modifiers = key.modifiers;
+        if matches!(key.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        KeyBinding { code: key.code, modifiers }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keybindings {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self::with_overrides(&HashMap::new())
+    }
+}
+
+impl Keybindings {
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyBinding::from_event(key)).copied()
+    }
+
+    /// Builds bindings from a `[keybindings]` table. Bad entries are skipped and described in
+    /// the returned errors, the rest still apply.
+    pub fn from_table(table: &toml::Table) -> (Keybindings, Vec<String>) {
+        let mut overrides = HashMap::new();
+        let mut errors = Vec::new();
+        for (name, value) in table {
+            let Some(action) = Action::from_name(name) else {
+                errors.push(format!("unknown action '{}'", name));
+                continue;
+            };
+            let keys: Vec<&str> = match value {
+                toml::Value::String(key) => vec![key.as_str()],
+                toml::Value::Array(keys) => keys.iter().filter_map(|key| key.as_str()).collect(),
+                _ => {
+                    errors.push(format!("{} must be a key or a list of keys", name));
+                    continue;
+                }
+            };
+            let mut parsed = Vec::new();
+            for key in &keys {
+                match KeyBinding::parse(key) {
+                    Some(binding) => parsed.push(binding),
+                    None => errors.push(format!("invalid key '{}' for {}", key, name)),
+                }
+            }
+            // an empty list unbinds the action, but only invalid keys keep the defaults
+            if parsed.is_empty() && !keys.is_empty() {
+                continue;
+            }
+            overrides.insert(action, parsed);
+        }
+        (Self::with_overrides(&overrides), errors)
+    }
+
+    fn with_overrides(overrides: &HashMap<Action, Vec<KeyBinding>>) -> Keybindings {
+        let mut bindings = HashMap::new();
+        for action in Action::ALL.into_iter().filter(|action| !overrides.contains_key(action)) {
+            for key in action.default_keys() {
+                if let Some(binding) = KeyBinding::parse(key) {
+                    bindings.insert(binding, action);
+                }
+            }
+        }
+        // configured keys go in last so they win over a default using the same key
+        for (action, keys) in overrides {
+            for binding in keys {
+                bindings.insert(*binding, *action);
+            }
+        }
+        Keybindings { bindings }
+    }
+}
+
+fn toml_keys(keys: &[&str]) -> String {
+    match keys {
+        [key] => format!("{:?}", key),
+        keys => format!("{:?}", keys),
+    }
+}
+
+/// A `[keybindings]` section listing every action with its default keys, meant as a starting
+/// point for the config file.
+pub fn reference_toml() -> String {
+    let mut out = String::from(
+        "# systop keybindings, copy this section into ~/.config/systop/config.toml and edit it.\n\
+         # Keys are single characters (\"q\", \"K\", \":\"), names (\"enter\", \"up\", \"f5\", \"space\")\n\
+         # or either with \"ctrl-\"/\"alt-\" in front. A list binds several keys, [] unbinds the action.\n\
+         \n\
+         [keybindings]\n",
+    );
+    for action in Action::ALL {
+        let keys = action.default_keys();
+        out.push_str(&format!("\n# {}\n", action.description()));
+        out.push_str(&format!("{} = {}\n", action.name(), toml_keys(keys)));
+        out.push_str(&format!("# {} = {}\n", action.name(), toml_keys(&[&format!("alt-{}", keys[0])])));
+    }
+    out
+}
@@ -1,4 +1,15 @@
 pub mod app;
+pub mod column;
+pub mod config;
+pub mod docker;
+pub mod filter;
+pub mod keybindings;
+#[cfg(feature = "http")]
+pub mod metrics_server;
+#[cfg(feature = "rhai")]
+pub mod rules;
+#[cfg(feature = "lua")]
+pub mod script;
 pub mod system;
 pub mod ui;
 
@@ -10,6 +21,48 @@ mod tests {
     use super::*;
     use std::time::Duration;
 
+    // an idle single-threaded process with nothing read from /proc, for tests that must not
+    // depend on what happens to be running on the host
+    fn test_process(pid: u32, ppid: u32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            name: format!("proc{}", pid),
+            user: "root".to_string(),
+            uid: Some(0),
+            cpu_usage: 0.0,
+            memory: 1024 * 1024,
+            virtual_memory: 4 * 1024 * 1024,
+            memory_percent: 0.1,
+            oom_score: None,
+            numa_node: None,
+            numa_cross_node: false,
+            cpu_affinity: None,
+            minor_faults_per_sec: 0,
+            major_faults_per_sec: 0,
+            kernel_thread: false,
+            is_new: false,
+            exited: false,
+            state: Some('S'),
+            state_changed_at: None,
+            hung: false,
+            swap_bytes: 0,
+            threads: 1,
+            nice: 0,
+            open_fds: None,
+            fd_limit: None,
+            health_score: 100,
+            capabilities: None,
+            io_priority: None,
+            executable_deleted: false,
+            disk_read_rate: 0,
+            disk_write_rate: 0,
+            start_time: 0,
+            gpu_usage: None,
+            gpu_memory: None,
+        }
+    }
+
     #[test]
     fn test_system_monitor_creation() {
         let monitor = SystemMonitor::new();
@@ -27,9 +80,514 @@ mod tests {
         assert!(!processes_memory.is_empty());
     }
 
+    #[test]
+    fn test_reset_history() {
+        let mut monitor = SystemMonitor::new();
+        for _ in 0..3 {
+            monitor.refresh();
+        }
+        assert!(!monitor.get_memory_history().is_empty());
+        assert_eq!(monitor.get_swap_history().len(), monitor.get_memory_history().len());
+        assert_eq!(monitor.get_disk_io_history().len(), monitor.get_memory_history().len());
+
+        monitor.reset_history();
+        assert!(monitor.get_memory_history().is_empty());
+        assert!(monitor.get_swap_history().is_empty());
+        assert!(monitor.get_disk_io_history().is_empty());
+        assert!(monitor.get_cpu_info().iter().all(|cpu| cpu.history.is_empty()));
+    }
+
+    #[test]
+    fn test_subscribe_receives_snapshots() {
+        let mut monitor = SystemMonitor::new();
+        let mut rx = monitor.subscribe();
+        monitor.refresh();
+
+        let snapshot = rx.try_recv().unwrap();
+        assert_eq!(snapshot.memory_total, monitor.get_total_memory());
+        assert!(!snapshot.processes.is_empty());
+    }
+
+    #[test]
+    fn test_process_info_from_sysinfo() {
+        use sysinfo::{PidExt, ProcessExt, SystemExt};
+
+        let system = sysinfo::System::new_all();
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let proc = system.process(pid).unwrap();
+
+        let info = ProcessInfo::from_with_total(proc, system.total_memory());
+        assert_eq!(info.pid, std::process::id());
+        assert_eq!(info.name, proc.name());
+        assert!(info.memory_percent > 0.0 && info.memory_percent <= 100.0);
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let monitor = SystemMonitor::new();
+        let baseline = monitor.snapshot();
+        let mut current: Vec<ProcessInfo> = baseline.processes.iter().filter(|p| !p.exited).cloned().collect();
+        assert!(current.len() >= 3);
+        assert!(baseline.diff(&current).is_empty());
+
+        let gone = current.remove(0);
+        current[0].memory += 2_000_000;
+        let grown = current[0].pid;
+        let mut spawned = current[1].clone();
+        spawned.pid = u32::MAX;
+        current.push(spawned);
+
+        let diff = baseline.diff(&current);
+        let pids: Vec<u32> = diff.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![grown, u32::MAX, gone.pid]);
+        assert!(diff[2].exited);
+    }
+
+    #[test]
+    fn test_csv_row_quoting() {
+        let monitor = SystemMonitor::new();
+        let mut process = monitor.get_processes(&SortOrder::Pid).remove(0);
+        process.name = "my \"app\", v2".to_string();
+
+        let row = process.to_csv_row();
+        assert!(row.contains(",\"my \"\"app\"\", v2\","));
+        assert_eq!(
+            row.matches(',').count() - 1,
+            ProcessInfo::csv_header().matches(',').count()
+        );
+    }
+
+    #[test]
+    fn test_health_score() {
+        use system::{compute_health_score, HealthScoreWeights, ProcessHistory};
+
+        let process = test_process(1, 0);
+        let weights = HealthScoreWeights::default();
+
+        let quiet = ProcessHistory {
+            cpu: [1.0, 2.0, 1.0].into(),
+            memory: [100, 100, 100].into(),
+        };
+        assert!(compute_health_score(&process, &quiet, &weights) >= 99);
+
+        let leaking = ProcessHistory {
+            cpu: [95.0, 99.0, 97.0].into(),
+            memory: [100, 150, 300].into(),
+        };
+        let score = compute_health_score(&process, &leaking, &weights);
+        assert!(score < 60);
+
+        // with every factor but cpu spikes ignored, only the spikes count
+        let cpu_only = HealthScoreWeights { memory_growth: 0.0, major_faults: 0.0, fd_usage: 0.0, threads: 0.0, ..weights };
+        assert_eq!(compute_health_score(&process, &leaking, &cpu_only), 0);
+    }
+
+    #[test]
+    fn test_memory_pressure() {
+        use system::{compute_memory_pressure, MemoryPressure, MemoryPressureWeights};
+
+        // default weights: memory 2, swap 1, major faults 1, dirty 0.5
+        let weights = MemoryPressureWeights::default();
+
+        // memory in use only counts past half of RAM, from there it climbs to full at 100%
+        assert_eq!(compute_memory_pressure(50.0, 0.0, 0, 0.0, &weights), MemoryPressure::None);
+        assert_eq!(compute_memory_pressure(75.0, 0.0, 0, 0.0, &weights), MemoryPressure::Low);
+        assert_eq!(compute_memory_pressure(100.0, 0.0, 0, 0.0, &weights), MemoryPressure::Medium);
+
+        // swap in use pushes full memory up a level, as does a fault storm
+        assert_eq!(compute_memory_pressure(100.0, 50.0, 0, 0.0, &weights), MemoryPressure::High);
+        assert_eq!(compute_memory_pressure(100.0, 0.0, 500, 0.0, &weights), MemoryPressure::High);
+        // the fault rate saturates at its ceiling instead of outweighing everything else
+        assert_eq!(compute_memory_pressure(100.0, 0.0, 50_000, 0.0, &weights), MemoryPressure::High);
+        assert_eq!(compute_memory_pressure(98.0, 90.0, 2000, 1.0, &weights), MemoryPressure::Critical);
+        assert!(MemoryPressure::High > MemoryPressure::Low);
+
+        // a negative weight is ignored rather than cancelling out the swap it would subtract
+        let negative = MemoryPressureWeights { swap: -5.0, ..weights };
+        assert_eq!(compute_memory_pressure(100.0, 100.0, 0, 0.0, &negative), MemoryPressure::High);
+        let unweighted = MemoryPressureWeights { memory: 0.0, swap: 0.0, major_faults: 0.0, dirty: 0.0 };
+        assert_eq!(compute_memory_pressure(100.0, 100.0, 5000, 1.0, &unweighted), MemoryPressure::None);
+    }
+
+    #[test]
+    fn test_cpu_efficiency() {
+        use system::CpuInfo;
+
+        assert_eq!(CpuInfo::efficiency(80.0, 1800, Some(3600)), 40.0);
+        assert_eq!(CpuInfo::efficiency(80.0, 3600, Some(3600)), 80.0);
+        // boost past the reported maximum doesn't count extra, an unknown maximum leaves usage as is
+        assert_eq!(CpuInfo::efficiency(50.0, 4000, Some(3600)), 50.0);
+        assert_eq!(CpuInfo::efficiency(50.0, 1800, None), 50.0);
+
+        let monitor = SystemMonitor::new();
+        assert_eq!(monitor.get_cpu_efficiency_scores().len(), monitor.get_cpu_info().len());
+    }
+
+    #[test]
+    fn test_build_process_tree() {
+        // 5's parent isn't listed, so it's a root of its own
+        let processes = [test_process(3, 1), test_process(1, 0), test_process(4, 2), test_process(2, 1), test_process(5, 99)];
+
+        let tree: Vec<(usize, u32)> = system::build_process_tree(&processes)
+            .into_iter()
+            .map(|(depth, p)| (depth, p.pid))
+            .collect();
+        // siblings keep the order they were passed in
+        assert_eq!(tree, vec![(0, 1), (1, 3), (1, 2), (2, 4), (0, 5)]);
+    }
+
+    #[test]
+    fn test_alerts_since() {
+        use system::AlertKind;
+
+        let mut monitor = SystemMonitor::new();
+        let (mut app, mut server) = (0, 0);
+        assert!(monitor.alerts_since(&mut app).is_empty());
+        monitor.raise_alert(AlertKind::Hung, 0, 0.0, "first".to_string());
+        monitor.raise_alert(AlertKind::Hung, std::process::id(), 97.5, "second".to_string());
+        let alerts = monitor.alerts_since(&mut app);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "first");
+        assert!(alerts[0].name.is_empty());
+        assert_eq!(alerts[1].seq, 1);
+        assert!(!alerts[1].name.is_empty());
+        assert_eq!(alerts[1].value, 97.5);
+        assert!(monitor.alerts_since(&mut app).is_empty());
+
+        // the app reading them doesn't take them from another consumer
+        monitor.raise_alert(AlertKind::Hung, 0, 0.0, "third".to_string());
+        let messages: Vec<String> = monitor.alerts_since(&mut server).into_iter().map(|alert| alert.message).collect();
+        assert_eq!(messages, ["first", "second", "third"]);
+        assert_eq!(monitor.alerts_since(&mut app)[0].message, "third");
+    }
+
+    #[test]
+    fn test_follow_watchlist() {
+        use app::FollowState;
+
+        let mut follow = FollowState::new(10);
+        follow.update(&[test_process(10, 1), test_process(12, 11), test_process(11, 10), test_process(20, 1)]);
+        assert_eq!(follow.watched_count(), 3);
+
+        // 11 exits, its child stays followed; the pid coming back for an unrelated process doesn't join
+        follow.update(&[test_process(10, 1), test_process(12, 1), test_process(20, 1)]);
+        assert_eq!(follow.watched_count(), 2);
+        follow.update(&[test_process(10, 1), test_process(12, 1), test_process(11, 20), test_process(20, 1)]);
+        assert_eq!(follow.watched_count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exited_process_kept() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut monitor = SystemMonitor::new();
+        monitor.refresh();
+        child.kill().unwrap();
+        child.wait().unwrap();
+        monitor.refresh();
+
+        let processes = monitor.get_processes_with_exited(&SortOrder::Pid);
+        let exited = processes.iter().find(|p| p.pid == pid).expect("exited process is still listed");
+        assert!(exited.exited);
+        assert_eq!(exited.name, "sleep");
+        assert!(monitor.get_processes(&SortOrder::Pid).iter().all(|p| p.pid != pid));
+    }
+
+    #[test]
+    fn test_page_navigation() {
+        use app::{page_down, page_up};
+
+        assert_eq!(page_down(0, 20, 100), 20);
+        assert_eq!(page_down(90, 20, 100), 99);
+        assert_eq!(page_down(99, 20, 100), 99);
+        assert_eq!(page_down(0, 20, 0), 0);
+        assert_eq!(page_up(30, 20), 10);
+        assert_eq!(page_up(5, 20), 0);
+        assert_eq!(page_up(0, 20), 0);
+        // a table squeezed to no rows still moves
+        assert_eq!(page_down(3, 0, 10), 4);
+        assert_eq!(page_up(3, 0), 2);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(ui::format_bytes(512), "512B");
+        assert_eq!(ui::format_bytes(1536), "1.5KB");
+        assert_eq!(ui::format_bytes(3 * 1024 * 1024 * 1024), "3.0GB");
+        assert_eq!(ui::format_rate(2 * 1024 * 1024), "2.0MB/s");
+    }
+
+    #[test]
+    fn test_start_time_sort() {
+        let monitor = SystemMonitor::new();
+        let processes = monitor.get_processes(&SortOrder::StartTime);
+        assert!(!processes.is_empty());
+        assert!(processes.windows(2).all(|pair| pair[0].start_time <= pair[1].start_time));
+        let mut newest_first = system::SortState::new(SortOrder::StartTime);
+        newest_first.direction = newest_first.direction.reversed();
+        assert_eq!(newest_first.direction, system::SortDirection::Descending);
+        let processes = monitor.get_sorted_processes(&newest_first);
+        assert!(processes.windows(2).all(|pair| pair[0].start_time >= pair[1].start_time));
+        assert_eq!(ui::format_age(9254), "2h 34m ago");
+        assert_eq!(ui::format_age(45), "45s ago");
+    }
+
+    #[test]
+    fn test_gpu_usage_sort() {
+        let mut processes = vec![test_process(1, 0), test_process(2, 1), test_process(3, 1), test_process(4, 1)];
+        processes[0].gpu_usage = None;
+        processes[1].gpu_usage = Some(5.0);
+        processes[2].gpu_usage = Some(40.0);
+        processes[3].gpu_usage = Some(0.0);
+
+        system::SortState::new(SortOrder::GpuUsage).sort(&mut processes);
+        // busiest first, an idle GPU client still before processes without one
+        assert_eq!(processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn test_sort_order_from_str() {
+        for order in SortOrder::ALL {
+            let name = order.to_string();
+            assert_eq!(name.parse::<SortOrder>(), Ok(order.clone()));
+            assert_eq!(name.to_uppercase().parse::<SortOrder>(), Ok(order));
+        }
+        assert_eq!("disk-read".parse::<SortOrder>(), Ok(SortOrder::DiskRead));
+        assert_eq!("CPU".parse::<SortOrder>(), Ok(SortOrder::Cpu));
+        let err = "size".parse::<SortOrder>().unwrap_err();
+        assert!(err.contains("cpu, memory, pid"), "{}", err);
+        assert!("".parse::<SortOrder>().is_err());
+        assert!("disk read".parse::<SortOrder>().is_err());
+    }
+
+    #[test]
+    fn test_filter_expr() {
+        use filter::{FilterExpr, FilterMode};
+
+        let substring = FilterExpr::parse("Rust").unwrap();
+        assert!(matches!(substring, FilterExpr::Substring(_)));
+        assert!(substring.matches("rust-analyzer"));
+        assert!(!substring.matches("cargo"));
+
+        let regex = FilterExpr::parse("/^rust.*er$/").unwrap();
+        assert!(matches!(regex, FilterExpr::Regex(_)));
+        assert!(regex.matches("Rust-Analyzer"));
+        assert!(!regex.matches("rustc"));
+
+        // a lone slash isn't a pattern
+        assert!(matches!(FilterExpr::parse("/").unwrap(), FilterExpr::Substring(_)));
+        let err = FilterExpr::parse("/(/").unwrap_err().to_string();
+        assert!(err.starts_with("invalid regex /(/"), "{}", err);
+        assert!(!err.contains('\n'));
+
+        // regex mode takes the pattern without slashes, the other modes read them as text
+        let regex = FilterMode::Regex("^sys".to_string()).compile().unwrap().unwrap();
+        assert!(regex.matches("systemd"));
+        assert!(!regex.matches("ssystemd"));
+        let search = FilterMode::Search("^sys".to_string()).compile().unwrap().unwrap();
+        assert!(!search.matches("systemd"));
+        assert!(FilterMode::Regex("(".to_string()).compile().is_err());
+        assert!(FilterMode::Exclude(String::new()).compile().unwrap().is_none());
+        assert!(FilterMode::Exclude("a".to_string()).hides());
+        assert!(!FilterMode::Search("a".to_string()).hides());
+    }
+
+    #[test]
+    fn test_keybindings_from_table() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use keybindings::{Action, Keybindings};
+
+        let table: toml::Table = "quit = \"x\"\nsort-cpu = [\"C\", \"ctrl-p\"]\nfly = \"y\"\nsort-memory = \"ctrl-\""
+            .parse()
+            .unwrap();
+        let (bindings, errors) = Keybindings::from_table(&table);
+        assert_eq!(errors.len(), 2);
+
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(bindings.action(&key(KeyCode::Char('x'), KeyModifiers::NONE)), Some(Action::Quit));
+        assert_eq!(bindings.action(&key(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+        assert_eq!(bindings.action(&key(KeyCode::Char('C'), KeyModifiers::SHIFT)), Some(Action::SortCpu));
+        assert_eq!(bindings.action(&key(KeyCode::Char('p'), KeyModifiers::CONTROL)), Some(Action::SortCpu));
+        assert_eq!(bindings.action(&key(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Action::MoveDown));
+        assert_eq!(bindings.action(&key(KeyCode::Char('m'), KeyModifiers::NONE)), Some(Action::SortMemory));
+
+        // the exported reference is a valid config that reproduces the defaults
+        let reference: toml::Table = keybindings::reference_toml().parse().unwrap();
+        let (bindings, errors) = Keybindings::from_table(reference["keybindings"].as_table().unwrap());
+        assert!(errors.is_empty());
+        assert_eq!(bindings, Keybindings::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_containers() {
+        use std::io::{Read, Write};
+
+        let socket = std::env::temp_dir().join(format!("systop-docker-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        // two polls of one container, the cpu counters advance by 1/4 of the system's in between
+        let server = std::thread::spawn(move || {
+            for (container_ns, system_ns) in [(1_000, 10_000), (3_000, 18_000)] {
+                for _ in 0..2 {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buf = [0; 512];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let len = stream.read(&mut buf).unwrap();
+                        assert!(len > 0, "request cut short");
+                        request.extend_from_slice(&buf[..len]);
+                    }
+                    let request = String::from_utf8(request).unwrap();
+                    let body = if request.starts_with("GET /containers/json ") {
+                        r#"[{"Id": "0123456789abcdef", "Names": ["/web"], "Status": "Up 2 hours"}]"#.to_string()
+                    } else {
+                        assert!(request.starts_with("GET /containers/0123456789abcdef/stats?"), "{}", request);
+                        format!(
+                            r#"{{"cpu_stats": {{"cpu_usage": {{"total_usage": {}}}, "system_cpu_usage": {}, "online_cpus": 2}},
+                                "memory_stats": {{"usage": 5000, "stats": {{"inactive_file": 1000}}}}}}"#,
+                            container_ns, system_ns
+                        )
+                    };
+                    write!(stream, "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}", body).unwrap();
+                }
+            }
+        });
+
+        let socket_path = socket.to_string_lossy().into_owned();
+        let mut samples = std::collections::HashMap::new();
+        let first = docker::list_containers(&socket_path, &mut samples).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].id, "0123456789ab");
+        assert_eq!(first[0].name, "web");
+        assert_eq!(first[0].status, "Up 2 hours");
+        assert_eq!(first[0].cpu_percent, 0.0);
+        assert_eq!(first[0].memory_bytes, 4000);
+
+        let second = docker::list_containers(&socket_path, &mut samples).unwrap();
+        assert_eq!(second[0].cpu_percent, 50.0);
+        server.join().unwrap();
+        std::fs::remove_file(&socket).unwrap();
+
+        let err = docker::list_containers(&socket_path, &mut samples).unwrap_err();
+        assert!(format!("{:#}", err).contains("failed to connect"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_column_order_config() {
+        use column::ProcessColumn;
+
+        let path = std::env::temp_dir().join(format!("systop-columns-{}.toml", std::process::id()));
+        std::fs::write(&path, "# kept\n[theme]\naccent = \"red\"\n").unwrap();
+        assert_eq!(config::load_column_order(&path).unwrap(), None);
+
+        let mut order = ProcessColumn::ALL.to_vec();
+        order.swap(0, 2);
+        config::save_column_order(&path, &order).unwrap();
+        assert_eq!(config::load_column_order(&path).unwrap(), Some(order));
+        let source = std::fs::read_to_string(&path).unwrap();
+        assert!(source.starts_with("# kept"));
+
+        // columns left out are appended in their default order
+        std::fs::write(&path, "[table]\ncolumns = [\"name\", \"pid\"]\n").unwrap();
+        let order = config::load_column_order(&path).unwrap().unwrap();
+        assert_eq!(order[..3], [ProcessColumn::Name, ProcessColumn::Pid, ProcessColumn::User]);
+        assert_eq!(order.len(), ProcessColumn::ALL.len());
+
+        // widths live next to the order without disturbing it
+        let widths = vec![(ProcessColumn::Pid, 4), (ProcessColumn::User, 16)];
+        config::save_column_widths(&path, &widths).unwrap();
+        assert_eq!(config::load_column_widths(&path).unwrap(), widths);
+        assert_eq!(config::load_column_order(&path).unwrap().unwrap()[0], ProcessColumn::Name);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_process_signal() {
+        use std::os::unix::process::ExitStatusExt;
+        use system::{SIGKILL, SIGTERM};
+
+        let monitor = SystemMonitor::new();
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        monitor.kill_process(child.id(), SIGTERM).unwrap();
+        assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+        // the pid is reaped now, and pids that would address a process group are refused
+        assert!(monitor.kill_process(child.id(), SIGKILL).is_err());
+        assert!(monitor.kill_process(0, SIGKILL).is_err());
+        assert!(monitor.kill_process(u32::MAX, SIGKILL).is_err());
+    }
+
+    #[test]
+    fn test_kill_dialog_transitions() {
+        use app::{AppMode, KillDialog};
+        use crossterm::event::KeyCode;
+        use system::{SIGKILL, SIGTERM};
+
+        let mut state = app::AppState::new(false);
+        let dialog = KillDialog::new(42, "worker".to_string());
+        state.mode = AppMode::KillDialog(dialog.clone());
+        assert!(state.handle_kill_key(KeyCode::Down).is_none());
+        assert!(state.handle_kill_key(KeyCode::Enter).is_none());
+        let AppMode::KillConfirm(picked) = &state.mode else {
+            panic!("Enter should ask for confirmation, got {:?}", state.mode);
+        };
+        assert_eq!(picked.signal(), SIGKILL);
+        assert_eq!(picked.prompt(), "Kill worker (PID 42)? [y/N]");
+
+        // anything but y backs out
+        assert!(state.handle_kill_key(KeyCode::Enter).is_none());
+        assert_eq!(state.mode, AppMode::Normal);
+
+        state.mode = AppMode::KillDialog(dialog.clone());
+        assert!(state.handle_kill_key(KeyCode::Esc).is_none());
+        assert_eq!(state.mode, AppMode::Normal);
+
+        state.mode = AppMode::KillDialog(dialog);
+        state.handle_kill_key(KeyCode::Enter);
+        let confirmed = state.handle_kill_key(KeyCode::Char('y')).unwrap();
+        assert_eq!((confirmed.pid, confirmed.signal()), (42, SIGTERM));
+        assert_eq!(state.mode, AppMode::Normal);
+
+        // keys outside the dialog leave the mode alone
+        state.mode = AppMode::FilterEntry;
+        assert!(state.handle_kill_key(KeyCode::Char('y')).is_none());
+        assert_eq!(state.mode, AppMode::FilterEntry);
+    }
+
     #[test]
     fn test_app_creation() {
-        let app = App::new(Duration::from_millis(1000), false);
+        let app = App::new(Duration::from_millis(1000), false, None);
         assert!(app.is_ok());
+        assert!(App::new(Duration::from_millis(1000), false, Some("sys".to_string())).is_ok());
+        assert!(App::new(Duration::from_millis(1000), false, Some("/(/".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_confirm_batch_kill() {
+        let monitor = SystemMonitor::new();
+        let mut state = app::AppState::new(false);
+        let pid = std::process::id();
+        state.toggle_mark(pid);
+        state.toggle_mark(u32::MAX);
+        state.confirm_batch_kill(&monitor);
+        let targets = state.kill_confirm.take().unwrap();
+        assert_eq!(targets.iter().map(|(pid, _)| *pid).collect::<Vec<_>>(), vec![pid]);
+        assert_eq!(state.selected_pids, std::collections::HashSet::from([pid]));
+
+        // nothing left to kill once every marked process is gone
+        state.toggle_mark(pid);
+        state.toggle_mark(u32::MAX);
+        state.confirm_batch_kill(&monitor);
+        assert!(state.kill_confirm.is_none());
+        assert!(state.selected_pids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_select_process_by_pid() {
+        let mut app = App::new(Duration::from_millis(1000), false, None).unwrap();
+        assert!(app.select_process_by_pid(std::process::id()).await);
+        assert!(!app.select_process_by_pid(u32::MAX).await);
     }
-}
\ No newline at end of file
+}
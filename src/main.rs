@@ -1,12 +1,25 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use std::path::Path;
 use std::time::Duration;
 
 mod app;
+mod column;
+mod config;
+mod docker;
+mod filter;
+mod keybindings;
+#[cfg(feature = "http")]
+mod metrics_server;
+#[cfg(feature = "rhai")]
+mod rules;
+#[cfg(feature = "lua")]
+mod script;
 mod system;
 mod ui;
 
 use app::App;
+use system::SortOrder;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -18,14 +31,155 @@ struct Cli {
     /// Enable debug mode
     #[arg(short, long)]
     debug: bool,
+
+    /// Start with the process list filtered by name, /pattern/ for a regex
+    #[arg(short = 'f', long, value_name = "FILTER")]
+    filter: Option<String>,
+
+    /// Maximum redraws per second (defaults to one redraw per update interval)
+    #[arg(long, value_name = "FPS")]
+    rate_limit_display: Option<u32>,
+
+    /// Slow down refreshes while total CPU usage is above 95%
+    #[arg(long)]
+    adaptive_interval: bool,
+
+    /// Only show processes inside this cgroup (v2 path, e.g. /user.slice/user-1000.slice)
+    #[arg(long, value_name = "CGROUP_PATH")]
+    cgroup_filter: Option<String>,
+
+    /// Hide processes owned by this user (name or numeric UID), may be given several times
+    #[arg(long, value_name = "USER", action = clap::ArgAction::Append)]
+    exclude_user: Vec<String>,
+
+    /// Never list this PID, may be given several times (more can be hidden with H or :hide)
+    #[arg(long, value_name = "PID", action = clap::ArgAction::Append)]
+    exclude_pid: Vec<u32>,
+
+    /// Seconds to wait for a too-small terminal to be resized before exiting
+    #[arg(long, value_name = "SECS", default_value = "30")]
+    resize_timeout: u64,
+
+    /// Run without the TUI until Ctrl-C (useful with --metrics-addr, --script or --rules)
+    #[arg(long)]
+    headless: bool,
+
+    /// With --headless, write every refresh to stdout as CSV
+    #[arg(long, requires = "headless")]
+    csv: bool,
+
+    /// Start with the least healthy processes on top
+    #[arg(long)]
+    alert_mode: bool,
+
+    /// Only list the first N processes in the sort order (also limits --csv output)
+    #[arg(long, value_name = "N")]
+    top_n: Option<usize>,
+
+    /// Column to sort the process list by at startup
+    #[arg(long, value_name = "COLUMN", conflicts_with = "alert_mode")]
+    sort: Option<SortOrder>,
+
+    /// Highlight processes whose executable has been deleted, e.g. replaced by a package upgrade
+    #[arg(long)]
+    show_deleted: bool,
+
+    /// Exit right away unless running as root (administrator on Windows)
+    #[arg(long)]
+    require_root: bool,
+
+    /// Follow a process and every child it forks
+    #[arg(long, value_name = "PID")]
+    follow: Option<u32>,
+
+    /// Only show this process and exit when it terminates
+    #[arg(long, value_name = "PID", conflicts_with = "follow")]
+    pid: Option<u32>,
+
+    /// Write our PID and start time to this file, removed again on exit (for process supervisors)
+    #[arg(long, value_name = "PATH")]
+    write_pid: Option<std::path::PathBuf>,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics and recent alerts at /alerts
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "ADDR")]
+    metrics_addr: Option<std::net::SocketAddr>,
+
+    /// Lua script with an on_refresh(processes, system) hook for custom alerts
+    #[cfg(feature = "lua")]
+    #[arg(long, value_name = "PATH")]
+    script: Option<std::path::PathBuf>,
+
+    /// Rhai rules file evaluated against every process after each refresh
+    #[cfg(feature = "rhai")]
+    #[arg(long, value_name = "PATH")]
+    rules: Option<std::path::PathBuf>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    if cli.require_root && !app::is_root() {
+        eprintln!("Error: this configuration requires root privileges. Run with sudo.");
+        std::process::exit(1);
+    }
     
-    let mut app = App::new(Duration::from_millis(cli.interval), cli.debug)?;
-    app.run().await?;
-    
-    Ok(())
+    let mut app = App::new(Duration::from_millis(cli.interval), cli.debug, cli.filter)?
+        .with_adaptive_interval(cli.adaptive_interval)
+        .with_alert_mode(cli.alert_mode)
+        .with_show_deleted(cli.show_deleted)
+        .with_excluded_users(cli.exclude_user)
+        .with_excluded_pids(cli.exclude_pid)
+        .with_resize_timeout(Duration::from_secs(cli.resize_timeout));
+    if let Some(sort) = cli.sort {
+        app = app.with_sort(sort);
+    }
+    if let Some(n) = cli.top_n {
+        app = app.with_top_n(n);
+    }
+    if let Some(fps) = cli.rate_limit_display {
+        app = app.with_render_interval(Duration::from_millis(1000 / u64::from(fps.max(1))));
+    }
+    if let Some(cgroup) = cli.cgroup_filter {
+        app = app.with_cgroup_filter(cgroup);
+    }
+    if let Some(pid) = cli.follow {
+        app = app.with_follow(pid);
+    }
+    if let Some(pid) = cli.pid {
+        app = app.with_watch_pid(pid);
+    }
+    #[cfg(feature = "http")]
+    if let Some(addr) = cli.metrics_addr {
+        app = app.with_metrics_addr(addr);
+    }
+    #[cfg(feature = "lua")]
+    if let Some(path) = cli.script {
+        app = app.with_script(&path)?;
+    }
+    #[cfg(feature = "rhai")]
+    if let Some(path) = cli.rules {
+        app = app.with_rules(&path)?;
+    }
+    if let Some(path) = &cli.write_pid {
+        write_pid_file(path)?;
+    }
+    let res = if cli.headless {
+        app.with_csv_export(cli.csv).run_headless().await
+    } else {
+        app.run().await
+    };
+    if let Some(path) = &cli.write_pid {
+        if let Err(err) = std::fs::remove_file(path) {
+            eprintln!("failed to remove pid file {}: {}", path.display(), err);
+        }
+    }
+
+    res
+}
+
+// first line is the pid, second the start time
+fn write_pid_file(path: &Path) -> Result<()> {
+    let contents = format!("{}\n{}\n", std::process::id(), chrono::Local::now().to_rfc3339());
+    std::fs::write(path, contents).with_context(|| format!("failed to write pid file {}", path.display()))
 }
\ No newline at end of file
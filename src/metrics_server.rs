@@ -0,0 +1,180 @@
+//! Prometheus metrics over HTTP, built with the `http` feature.
+//!
+//! `MetricsServer` only needs a shared `SystemMonitor`, so it can run inside any tokio
+//! runtime without an `App`:
+//!
+//! ```no_run
+//! # async fn example() {
+//! use std::sync::Arc;
+//! use systop::{metrics_server::MetricsServer, SystemMonitor};
+//! use tokio::sync::RwLock;
+//!
+//! let monitor = Arc::new(RwLock::new(SystemMonitor::new()));
+//! let server = MetricsServer::new("127.0.0.1:9100".parse().unwrap(), Arc::clone(&monitor));
+//! let handle = server.run();
+//! // keep calling monitor.write().await.refresh() elsewhere, then GET /metrics
+//! # handle.abort();
+//! # }
+//! ```
+//!
+//! The server does not refresh the monitor itself, it reports whatever the last refresh saw.
+//! `GET /alerts` lists the alerts the monitor still retains as JSON, without taking them from
+//! the app or any other consumer.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::system::{SortOrder, SystemMonitor};
+
+/// How long a client gets to send its request line before the connection is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct MetricsServer {
+    addr: SocketAddr,
+    monitor: Arc<RwLock<SystemMonitor>>,
+}
+
+impl MetricsServer {
+    pub fn new(addr: SocketAddr, monitor: Arc<RwLock<SystemMonitor>>) -> Self {
+        Self { addr, monitor }
+    }
+
+    /// Binds and serves `GET /metrics` and `GET /alerts` until the task is aborted. Bind errors are logged
+    /// and end the task.
+    pub fn run(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let listener = match TcpListener::bind(self.addr).await {
+                Ok(listener) => listener,
+                Err(err) => {
+                    error!(addr = %self.addr, "failed to bind metrics server: {}", err);
+                    return;
+                }
+            };
+            info!(addr = %self.addr, "metrics server listening");
+
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        warn!("failed to accept metrics connection: {}", err);
+                        continue;
+                    }
+                };
+                let monitor = Arc::clone(&self.monitor);
+                tokio::spawn(async move {
+                    if let Err(err) = handle_connection(stream, monitor).await {
+                        warn!(%peer, "metrics request failed: {}", err);
+                    }
+                });
+            }
+        })
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, monitor: Arc<RwLock<SystemMonitor>>) -> std::io::Result<()> {
+    // only the request line matters, headers and body are ignored
+    let mut buf = [0u8; 4096];
+    let n = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut buf))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "no request received"))??;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+
+    const TEXT: &str = "text/plain; version=0.0.4";
+    let (status, content_type, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", TEXT, render_metrics(&*monitor.read().await)),
+        (Some("GET"), Some("/alerts")) => ("200 OK", "application/json", render_alerts(&*monitor.read().await)),
+        (Some("GET"), _) => ("404 Not Found", TEXT, "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", TEXT, "method not allowed\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The alerts the monitor retains, oldest first. `seq` lets a poller skip the ones it has seen.
+pub fn render_alerts(monitor: &SystemMonitor) -> String {
+    // a fresh cursor every request, the server keeps no state between polls
+    let alerts: Vec<serde_json::Value> = monitor
+        .alerts_since(&mut 0)
+        .into_iter()
+        .map(|alert| {
+            serde_json::json!({
+                "seq": alert.seq,
+                "kind": alert.kind.name(),
+                "pid": alert.pid,
+                "name": alert.name,
+                "value": alert.value,
+                "message": alert.message,
+                "age_seconds": alert.timestamp.elapsed().as_secs_f64(),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(alerts).to_string() + "\n"
+}
+
+/// Prometheus text exposition of the monitor's current state.
+pub fn render_metrics(monitor: &SystemMonitor) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(out, "# HELP systop_{} {}", name, help);
+        let _ = writeln!(out, "# TYPE systop_{} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "systop_{}{} {}", name, labels, value);
+        }
+    };
+
+    gauge("cpu_usage_percent", "Total CPU usage.", &[(String::new(), monitor.get_total_cpu_usage() as f64)]);
+    let per_cpu: Vec<(String, f64)> = monitor
+        .get_cpu_info()
+        .iter()
+        .enumerate()
+        .map(|(i, cpu)| (format!("{{cpu=\"{}\"}}", i), cpu.usage as f64))
+        .collect();
+    gauge("cpu_core_usage_percent", "Per-core CPU usage.", &per_cpu);
+    gauge("memory_total_bytes", "Total memory.", &[(String::new(), monitor.get_total_memory() as f64)]);
+    gauge("memory_used_bytes", "Used memory.", &[(String::new(), monitor.get_used_memory() as f64)]);
+    gauge(
+        "disk_read_bytes_per_second",
+        "Disk read rate across all disks.",
+        &[(String::new(), monitor.get_total_disk_read_rate() as f64)],
+    );
+    gauge(
+        "disk_write_bytes_per_second",
+        "Disk write rate across all disks.",
+        &[(String::new(), monitor.get_total_disk_write_rate() as f64)],
+    );
+    let (rx, tx) = monitor.get_total_network_rate();
+    gauge(
+        "network_bytes_per_second",
+        "Network throughput across all interfaces except loopback.",
+        &[
+            ("{direction=\"rx\"}".to_string(), rx as f64),
+            ("{direction=\"tx\"}".to_string(), tx as f64),
+        ],
+    );
+    if let Some(entropy) = monitor.get_entropy_pool_size() {
+        gauge("entropy_available_bits", "Bits in the kernel entropy pool.", &[(String::new(), entropy as f64)]);
+    }
+    gauge(
+        "processes",
+        "Number of user space processes.",
+        &[(String::new(), monitor.get_processes(&SortOrder::Pid).len() as f64)],
+    );
+
+    out
+}
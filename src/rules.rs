@@ -0,0 +1,117 @@
+//! Rhai rules for `--rules`, only built with the `rhai` feature.
+//!
+//! The rules file is run once per process after every refresh with `process` in scope, e.g.
+//! `if process.cpu > 90 && process.name == "myapp" { kill(process.pid) }`.
+//! `alert(message)`, `alert(message, value)`, `notify(message)` and `kill(pid)` are available,
+//! alerts are about the process the rules are running for.
+
+use anyhow::{anyhow, Context, Result};
+use rhai::{Engine, Scope, AST, INT};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::system::{ProcessInfo, SortOrder, SystemMonitor, SIGKILL};
+
+#[derive(Debug, Clone)]
+pub enum RuleEvent {
+    Alert { message: String, pid: u32, value: f64 },
+    Notify(String),
+}
+
+#[derive(Default)]
+struct Pending {
+    events: Vec<RuleEvent>,
+    kills: Vec<u32>,
+    current_pid: u32, // the process the rules are running for
+}
+
+pub struct RulesEngine {
+    engine: Engine,
+    ast: AST,
+    pending: Arc<Mutex<Pending>>,
+}
+
+impl RulesEngine {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read rules {}", path.display()))?;
+
+        let pending = Arc::new(Mutex::new(Pending::default()));
+        let engine = build_engine(&pending);
+        let ast = engine
+            .compile(&source)
+            .map_err(|err| anyhow!("failed to parse rules {}: {}", path.display(), err))?;
+
+        Ok(Self { engine, ast, pending })
+    }
+
+    /// Runs the rules against every process, applies any `kill` requests and returns the messages raised.
+    pub fn evaluate(&self, monitor: &SystemMonitor) -> Result<Vec<RuleEvent>> {
+        let mut result = Ok(());
+        for process in monitor.get_processes(&SortOrder::Pid) {
+            let pid = process.pid;
+            self.pending.lock().unwrap().current_pid = pid;
+            let mut scope = Scope::new();
+            scope.push("process", process);
+            if let Err(err) = self.engine.run_ast_with_scope(&mut scope, &self.ast) {
+                // keep going so one bad process doesn't disable the rules for everything else
+                result = Err(anyhow!("rule failed for pid {}: {}", pid, err));
+            }
+        }
+
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for pid in pending.kills {
+            if let Err(err) = monitor.kill_process(pid, SIGKILL) {
+                tracing::warn!(pid, "rule kill failed: {:#}", err);
+            }
+        }
+        result.map(|_| pending.events)
+    }
+}
+
+fn build_engine(pending: &Arc<Mutex<Pending>>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<ProcessInfo>("Process")
+        .register_get("pid", |p: &mut ProcessInfo| p.pid as INT)
+        .register_get("ppid", |p: &mut ProcessInfo| p.ppid as INT)
+        .register_get("name", |p: &mut ProcessInfo| p.name.clone())
+        .register_get("cpu", |p: &mut ProcessInfo| p.cpu_usage as f64)
+        .register_get("memory", |p: &mut ProcessInfo| p.memory as INT)
+        .register_get("memory_percent", |p: &mut ProcessInfo| p.memory_percent as f64)
+        .register_get("oom_score", |p: &mut ProcessInfo| p.oom_score.unwrap_or(0) as INT)
+        .register_get("major_faults", |p: &mut ProcessInfo| p.major_faults_per_sec as INT);
+
+    let queue = Arc::clone(pending);
+    engine.register_fn("alert", move |message: &str| {
+        let mut pending = queue.lock().unwrap();
+        let pid = pending.current_pid;
+        pending.events.push(RuleEvent::Alert { message: message.to_string(), pid, value: 0.0 });
+    });
+
+    let queue = Arc::clone(pending);
+    engine.register_fn("alert", move |message: &str, value: f64| {
+        let mut pending = queue.lock().unwrap();
+        let pid = pending.current_pid;
+        pending.events.push(RuleEvent::Alert { message: message.to_string(), pid, value });
+    });
+
+    let queue = Arc::clone(pending);
+    engine.register_fn("notify", move |message: &str| {
+        queue.lock().unwrap().events.push(RuleEvent::Notify(message.to_string()));
+    });
+
+    // deferred like the lua hook; a rule may name the same pid while looking at several processes
+    let queue = Arc::clone(pending);
+    engine.register_fn("kill", move |pid: INT| {
+        let mut pending = queue.lock().unwrap();
+        if let Ok(pid) = u32::try_from(pid) {
+            if !pending.kills.contains(&pid) {
+                pending.kills.push(pid);
+            }
+        }
+    });
+
+    engine
+}
@@ -0,0 +1,137 @@
+//! Lua hooks for `--script`, only built with the `lua` feature.
+//!
+//! The script may define `on_refresh(processes, system)`, which is called after every
+//! `SystemMonitor::refresh`. It can call `alert(message[, pid[, value]])`, `notify(message)` and
+//! `kill(pid)`.
+
+use anyhow::{Context, Result};
+use mlua::{Function, Lua, Table};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::system::{ProcessInfo, SortOrder, SystemMonitor, SIGKILL};
+
+#[derive(Debug, Clone)]
+pub enum ScriptEvent {
+    Alert { message: String, pid: u32, value: f64 }, // pid 0 and value 0 when left out
+    Notify(String),
+}
+
+#[derive(Default)]
+struct Pending {
+    events: Vec<ScriptEvent>,
+    kills: Vec<u32>,
+}
+
+pub struct ScriptEngine {
+    lua: Lua,
+    pending: Arc<Mutex<Pending>>,
+}
+
+impl ScriptEngine {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read script {}", path.display()))?;
+
+        let lua = Lua::new();
+        let pending = Arc::new(Mutex::new(Pending::default()));
+        register_functions(&lua, &pending)?;
+
+        lua.load(&source)
+            .set_name(path.display().to_string())
+            .exec()
+            .with_context(|| format!("failed to run script {}", path.display()))?;
+
+        Ok(Self { lua, pending })
+    }
+
+    /// Calls the script's `on_refresh`, applies any `kill` requests and returns the messages it raised.
+    pub fn on_refresh(&self, monitor: &SystemMonitor) -> Result<Vec<ScriptEvent>> {
+        let Some(on_refresh) = self.lua.globals().get::<_, Option<Function>>("on_refresh")? else {
+            return Ok(Vec::new());
+        };
+
+        let processes = self.lua.create_table()?;
+        for (i, process) in monitor.get_processes(&SortOrder::Pid).iter().enumerate() {
+            processes.set(i + 1, self.process_table(process)?)?;
+        }
+        let system = self.system_table(monitor)?;
+        on_refresh
+            .call::<_, ()>((processes, system))
+            .context("on_refresh failed")?;
+
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for pid in pending.kills {
+            if let Err(err) = monitor.kill_process(pid, SIGKILL) {
+                tracing::warn!(pid, "script kill failed: {:#}", err);
+            }
+        }
+        Ok(pending.events)
+    }
+
+    fn process_table(&self, process: &ProcessInfo) -> mlua::Result<Table<'_>> {
+        let table = self.lua.create_table()?;
+        table.set("pid", process.pid)?;
+        table.set("ppid", process.ppid)?;
+        table.set("name", process.name.as_str())?;
+        table.set("cpu_usage", process.cpu_usage)?;
+        table.set("memory", process.memory)?;
+        table.set("memory_percent", process.memory_percent)?;
+        table.set("oom_score", process.oom_score)?;
+        table.set("major_faults_per_sec", process.major_faults_per_sec)?;
+        Ok(table)
+    }
+
+    fn system_table(&self, monitor: &SystemMonitor) -> mlua::Result<Table<'_>> {
+        let info = monitor.get_system_info();
+        let table = self.lua.create_table()?;
+        table.set("hostname", info.hostname)?;
+        table.set("kernel_version", info.kernel_version)?;
+        table.set("os_version", info.os_version)?;
+        table.set("uptime", info.uptime)?;
+        table.set("cpu_usage", monitor.get_total_cpu_usage())?;
+        table.set("memory_total", monitor.get_total_memory())?;
+        table.set("memory_used", monitor.get_used_memory())?;
+        table.set("memory_percent", monitor.get_memory_percent())?;
+        table.set("entropy_avail", monitor.get_entropy_pool_size())?;
+        Ok(table)
+    }
+}
+
+fn register_functions(lua: &Lua, pending: &Arc<Mutex<Pending>>) -> Result<()> {
+    let globals = lua.globals();
+
+    let queue = Arc::clone(pending);
+    globals.set(
+        "alert",
+        lua.create_function(move |_, (message, pid, value): (String, Option<u32>, Option<f64>)| {
+            queue.lock().unwrap().events.push(ScriptEvent::Alert {
+                message,
+                pid: pid.unwrap_or(0),
+                value: value.unwrap_or(0.0),
+            });
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(pending);
+    globals.set(
+        "notify",
+        lua.create_function(move |_, message: String| {
+            queue.lock().unwrap().events.push(ScriptEvent::Notify(message));
+            Ok(())
+        })?,
+    )?;
+
+    // kills are deferred until on_refresh returns so the script sees a consistent snapshot
+    let queue = Arc::clone(pending);
+    globals.set(
+        "kill",
+        lua.create_function(move |_, pid: u32| {
+            queue.lock().unwrap().kills.push(pid);
+            Ok(())
+        })?,
+    )?;
+
+    Ok(())
+}
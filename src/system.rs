@@ -1,13 +1,219 @@
-use std::collections::VecDeque;
-use sysinfo::{CpuExt, PidExt, ProcessExt, System, SystemExt};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sysinfo::{CpuExt, DiskExt, NetworkExt, NetworksExt, Pid, PidExt, Process, ProcessExt, System, SystemExt, UserExt};
+use tokio::sync::{broadcast, watch};
 
-#[derive(Debug, Clone)]
+use crate::docker::ContainerInfo;
+use crate::filter::FilterExpr;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ProcessInfo {
     pub pid: u32,
+    pub ppid: u32, // 0 when the process has no parent
     pub name: String,
+    pub user: String, // owner's name, or the numeric uid when it can't be resolved
+    pub uid: Option<u32>, // unix only
     pub cpu_usage: f32,
     pub memory: u64,
+    pub virtual_memory: u64,
     pub memory_percent: f32,
+    pub oom_score: Option<i32>, // linux only
+    pub numa_node: Option<u32>, // node holding most of the process's pages
+    pub numa_cross_node: bool,  // running on a cpu outside numa_node
+    pub cpu_affinity: Option<Vec<u32>>,
+    pub minor_faults_per_sec: u64,
+    pub major_faults_per_sec: u64,
+    pub kernel_thread: bool,
+    pub is_new: bool, // appeared since the previous refresh
+    pub exited: bool, // gone, this is the last data seen before it exited
+    pub state: Option<char>, // R, S, D, Z, ... from /proc/<pid>/stat
+    #[serde(skip)]
+    pub state_changed_at: Option<Instant>, // None until a transition has been observed
+    pub hung: bool, // in uninterruptible sleep (D) for longer than HUNG_STATE_THRESHOLD
+    pub swap_bytes: u64,
+    pub threads: u32,
+    pub nice: i32, // -20 (highest priority) to 19
+    pub open_fds: Option<u32>, // None when /proc/<pid>/fd isn't readable, i.e. another user's process
+    pub fd_limit: Option<u64>, // soft RLIMIT_NOFILE
+    pub health_score: u8,      // 0 (worst) to 100, see compute_health_score
+    pub capabilities: Option<CapabilitySet>,
+    pub io_priority: Option<IoPriority>, // linux only
+    pub executable_deleted: bool, // the binary was replaced or removed since it started, e.g. by an upgrade
+    pub disk_read_rate: u64, // bytes per second over the last refresh interval
+    pub disk_write_rate: u64,
+    pub start_time: u64, // seconds since the unix epoch
+    pub gpu_usage: Option<f32>, // busiest GPU engine in percent, None without the gpu feature or a GPU client
+    pub gpu_memory: Option<u64>, // VRAM in bytes
+}
+
+// identity is the pid alone, the rest is a snapshot that changes every refresh
+impl PartialEq for ProcessInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid == other.pid
+    }
+}
+
+impl Eq for ProcessInfo {}
+
+impl std::hash::Hash for ProcessInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.pid.hash(state);
+    }
+}
+
+/// A sysinfo process plus the total memory needed for `memory_percent`.
+pub struct WithTotalMemory<'a>(pub &'a Process, pub u64);
+
+/// Only what sysinfo knows; the /proc extras are filled in by `SystemMonitor`.
+impl From<WithTotalMemory<'_>> for ProcessInfo {
+    fn from(WithTotalMemory(proc, total_memory): WithTotalMemory<'_>) -> Self {
+        ProcessInfo {
+            pid: proc.pid().as_u32(),
+            ppid: proc.parent().map(|p| p.as_u32()).unwrap_or(0),
+            name: proc.name().to_string(),
+            user: proc.user_id().map(|uid| (**uid).to_string()).unwrap_or_default(),
+            uid: process_uid(proc),
+            cpu_usage: proc.cpu_usage(),
+            memory: proc.memory(),
+            virtual_memory: proc.virtual_memory(),
+            memory_percent: (proc.memory() as f32 / total_memory as f32) * 100.0,
+            oom_score: None,
+            numa_node: None,
+            numa_cross_node: false,
+            cpu_affinity: None,
+            minor_faults_per_sec: 0,
+            major_faults_per_sec: 0,
+            kernel_thread: is_kernel_thread(proc),
+            is_new: false,
+            exited: false,
+            state: None,
+            state_changed_at: None,
+            hung: false,
+            swap_bytes: 0,
+            threads: 0,
+            nice: 0,
+            open_fds: None,
+            fd_limit: None,
+            health_score: 100,
+            capabilities: None,
+            io_priority: None,
+            executable_deleted: false,
+            disk_read_rate: 0,
+            disk_write_rate: 0,
+            start_time: proc.start_time(),
+            gpu_usage: None,
+            gpu_memory: None,
+        }
+    }
+}
+
+// sysinfo's Uid is a SID string on windows
+#[cfg(unix)]
+fn process_uid(proc: &Process) -> Option<u32> {
+    proc.user_id().map(|uid| **uid)
+}
+
+#[cfg(not(unix))]
+fn process_uid(_proc: &Process) -> Option<u32> {
+    None
+}
+
+/// `processes` in depth-first order, each paired with its depth. Children follow their parent
+/// in the order they appear in `processes`; processes whose parent isn't listed are roots.
+pub fn build_process_tree(processes: &[ProcessInfo]) -> Vec<(usize, ProcessInfo)> {
+    let listed: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+    for process in processes {
+        children.entry(process.ppid).or_default().push(process);
+    }
+
+    let mut rows = Vec::with_capacity(processes.len());
+    // pid 0 is its own parent on some systems, don't loop on it
+    let mut stack: Vec<(usize, &ProcessInfo)> = processes
+        .iter()
+        .rev()
+        .filter(|p| !listed.contains(&p.ppid) || p.ppid == p.pid)
+        .map(|p| (0, p))
+        .collect();
+    while let Some((depth, process)) = stack.pop() {
+        rows.push((depth, process.clone()));
+        if let Some(kids) = children.get(&process.pid) {
+            stack.extend(kids.iter().rev().filter(|kid| kid.pid != process.pid).map(|kid| (depth + 1, *kid)));
+        }
+    }
+    rows
+}
+
+impl ProcessInfo {
+    pub fn from_with_total(proc: &Process, total_memory: u64) -> Self {
+        WithTotalMemory(proc, total_memory).into()
+    }
+
+    /// RSS / VMS. Low values mean mostly mapped-but-untouched memory, e.g. big mmapped files
+    /// or shared libraries; nothing mapped at all counts as fully efficient.
+    pub fn virtual_memory_ratio(&self) -> f32 {
+        if self.virtual_memory == 0 {
+            return 1.0;
+        }
+        self.memory as f32 / self.virtual_memory as f32
+    }
+
+    fn apply_proc_stats(&mut self, stats: &ProcStats) {
+        self.oom_score = stats.oom_score;
+        self.numa_node = stats.numa_node;
+        self.numa_cross_node = stats.numa_cross_node;
+        self.cpu_affinity = stats.cpu_affinity.clone();
+        self.minor_faults_per_sec = stats.minor_faults_per_sec;
+        self.major_faults_per_sec = stats.major_faults_per_sec;
+        self.state = stats.state;
+        self.state_changed_at = stats.state_changed_at;
+        self.hung = stats.is_hung();
+        self.swap_bytes = stats.swap_bytes;
+        self.threads = stats.threads;
+        self.nice = stats.nice;
+        self.open_fds = stats.open_fds;
+        self.fd_limit = stats.fd_limit;
+        self.capabilities = stats.capabilities;
+        self.io_priority = stats.io_priority;
+        self.executable_deleted = stats.executable_deleted;
+        self.gpu_usage = stats.gpu_usage;
+        self.gpu_memory = stats.gpu_memory;
+    }
+
+    pub fn csv_header() -> &'static str {
+        "pid,ppid,name,cpu_usage,memory,memory_percent,oom_score,numa_node,minor_faults_per_sec,major_faults_per_sec"
+    }
+
+    /// One RFC 4180 row matching `csv_header`, without the trailing line break.
+    pub fn to_csv_row(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        format!(
+            "{},{},{},{:.1},{},{:.2},{},{},{},{}",
+            self.pid,
+            self.ppid,
+            csv_field(&self.name),
+            self.cpu_usage,
+            self.memory,
+            self.memory_percent,
+            optional(self.oom_score.map(|s| s.to_string())),
+            optional(self.numa_node.map(|n| n.to_string())),
+            self.minor_faults_per_sec,
+            self.major_faults_per_sec,
+        )
+    }
+}
+
+// quote fields containing a separator, quote or line break, doubling embedded quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +221,23 @@ pub struct CpuInfo {
     pub name: String,
     pub usage: f32,
     pub history: VecDeque<f32>, // keep last 60 readings for sparkline
+    pub frequency_mhz: u64,
+    pub max_frequency_mhz: Option<u64>, // from cpufreq, None where the kernel doesn't expose it
+    pub efficiency_score: f32, // usage scaled by frequency / max frequency, see CpuInfo::efficiency
+    pub governor: Option<String>, // cpufreq scaling governor, e.g. "schedutil"
+    pub scaling_range_mhz: Option<(u64, u64)>, // limits the governor keeps the frequency within
+}
+
+impl CpuInfo {
+    /// Usage scaled by how fast the core runs compared to its maximum, so 100% on a core
+    /// clocked at half speed (an efficiency core, or one held back) counts as 50. Plain usage
+    /// when the maximum is unknown.
+    pub fn efficiency(usage: f32, frequency_mhz: u64, max_frequency_mhz: Option<u64>) -> f32 {
+        match max_frequency_mhz {
+            Some(max) if max > 0 => usage * (frequency_mhz as f32 / max as f32).min(1.0),
+            _ => usage,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,13 +246,901 @@ pub enum SortOrder {
     Memory,
     Pid,
     Name,
+    OomScore,
+    MajorFaults,
+    MemoryEfficiency,
+    HealthScore,
+    Threads,
+    DiskRead,
+    DiskWrite,
+    User,
+    StartTime,
+    Nice,
+    GpuUsage,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 15] = [
+        SortOrder::Cpu,
+        SortOrder::Memory,
+        SortOrder::Pid,
+        SortOrder::Name,
+        SortOrder::Threads,
+        SortOrder::User,
+        SortOrder::DiskRead,
+        SortOrder::DiskWrite,
+        SortOrder::OomScore,
+        SortOrder::MajorFaults,
+        SortOrder::MemoryEfficiency,
+        SortOrder::HealthScore,
+        SortOrder::StartTime,
+        SortOrder::Nice,
+        SortOrder::GpuUsage,
+    ];
+
+    /// The name `--sort` takes, e.g. "disk-read".
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Cpu => "cpu",
+            SortOrder::Memory => "memory",
+            SortOrder::Pid => "pid",
+            SortOrder::Name => "name",
+            SortOrder::Threads => "threads",
+            SortOrder::User => "user",
+            SortOrder::DiskRead => "disk-read",
+            SortOrder::DiskWrite => "disk-write",
+            SortOrder::OomScore => "oom-score",
+            SortOrder::MajorFaults => "major-faults",
+            SortOrder::MemoryEfficiency => "memory-efficiency",
+            SortOrder::HealthScore => "health",
+            SortOrder::StartTime => "start-time",
+            SortOrder::Nice => "nice",
+            SortOrder::GpuUsage => "gpu",
+        }
+    }
+
+    /// The direction `get_processes` sorts this column in, the one that puts the interesting
+    /// processes on top.
+    pub fn default_direction(&self) -> SortDirection {
+        match self {
+            SortOrder::Cpu
+            | SortOrder::Memory
+            | SortOrder::OomScore
+            | SortOrder::MajorFaults
+            | SortOrder::Threads
+            | SortOrder::DiskRead
+            | SortOrder::DiskWrite
+            | SortOrder::GpuUsage => SortDirection::Descending,
+            SortOrder::Pid
+            | SortOrder::Name
+            | SortOrder::User
+            | SortOrder::StartTime
+            | SortOrder::Nice
+            | SortOrder::MemoryEfficiency
+            | SortOrder::HealthScore => SortDirection::Ascending,
+        }
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SortOrder::ALL
+            .into_iter()
+            .find(|order| order.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = SortOrder::ALL.iter().map(SortOrder::name).collect();
+                format!("expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        }
+    }
+
+    /// Arrow for the table title.
+    pub fn arrow(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "↑",
+            SortDirection::Descending => "↓",
+        }
+    }
+}
+
+/// The column the process table is sorted by and which way round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortState {
+    pub order: SortOrder,
+    pub direction: SortDirection,
+}
+
+impl SortState {
+    /// `order` in its default direction.
+    pub fn new(order: SortOrder) -> Self {
+        let direction = order.default_direction();
+        Self { order, direction }
+    }
+
+    /// Sorts `processes` the way `SystemMonitor::get_sorted_processes` would.
+    pub fn sort(&self, processes: &mut [ProcessInfo]) {
+        sort_processes(processes, &self.order);
+        if self.direction != self.order.default_direction() {
+            processes.reverse();
+        }
+    }
+}
+
+/// Recent samples of one process, oldest first, for trends the current values can't show.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessHistory {
+    pub cpu: VecDeque<f32>,
+    pub memory: VecDeque<u64>,
+}
+
+impl ProcessHistory {
+    fn push(&mut self, cpu: f32, memory: u64, max_len: usize) {
+        if self.cpu.len() >= max_len {
+            self.cpu.pop_front();
+            self.memory.pop_front();
+        }
+        self.cpu.push_back(cpu);
+        self.memory.push_back(memory);
+    }
+}
+
+/// How much each factor counts towards the health score, only the ratios matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthScoreWeights {
+    pub cpu_spikes: f32,
+    pub memory_growth: f32,
+    pub major_faults: f32,
+    pub fd_usage: f32,
+    pub threads: f32,
+}
+
+impl Default for HealthScoreWeights {
+    fn default() -> Self {
+        Self {
+            cpu_spikes: 1.0,
+            memory_growth: 1.0,
+            major_faults: 1.0,
+            fd_usage: 1.0,
+            threads: 0.5,
+        }
+    }
+}
+
+// the point at which each factor counts as fully unhealthy
+const CPU_SPIKE_THRESHOLD: f32 = 90.0; // percent of one core
+const MAJOR_FAULT_RATE_CEILING: f64 = 1000.0; // per second
+const THREAD_COUNT_CEILING: f64 = 1000.0;
+
+/// 100 for a quiet process, towards 0 the more often its CPU spikes, the faster its memory
+/// grows over `history`, the more it faults to disk, the closer it is to its open file limit
+/// and the more threads it has.
+pub fn compute_health_score(info: &ProcessInfo, history: &ProcessHistory, weights: &HealthScoreWeights) -> u8 {
+    let cpu_spikes = if history.cpu.is_empty() {
+        0.0
+    } else {
+        history.cpu.iter().filter(|&&cpu| cpu > CPU_SPIKE_THRESHOLD).count() as f64 / history.cpu.len() as f64
+    };
+    // doubling over the window is as bad as it gets
+    let memory_growth = match (history.memory.front(), history.memory.back()) {
+        (Some(&first), Some(&last)) if first > 0 => last.saturating_sub(first) as f64 / first as f64,
+        _ => 0.0,
+    };
+    let major_faults = info.major_faults_per_sec as f64 / MAJOR_FAULT_RATE_CEILING;
+    let fd_usage = match (info.open_fds, info.fd_limit) {
+        (Some(open), Some(limit)) if limit > 0 => open as f64 / limit as f64,
+        _ => 0.0,
+    };
+    let threads = info.threads as f64 / THREAD_COUNT_CEILING;
+
+    let penalty = weighted_score(&[
+        (cpu_spikes, weights.cpu_spikes),
+        (memory_growth, weights.memory_growth),
+        (major_faults, weights.major_faults),
+        (fd_usage, weights.fd_usage),
+        (threads, weights.threads),
+    ]);
+    (100.0 * (1.0 - penalty)).round() as u8
+}
+
+/// The weighted mean of `(value, weight)` pairs, values clamped to 0..=1 and negative weights
+/// ignored. 0 when no weight is left.
+fn weighted_score(factors: &[(f64, f32)]) -> f64 {
+    let total_weight: f64 = factors.iter().map(|(_, weight)| weight.max(0.0) as f64).sum();
+    if total_weight == 0.0 {
+        return 0.0;
+    }
+    factors
+        .iter()
+        .map(|(value, weight)| weight.max(0.0) as f64 * value.clamp(0.0, 1.0))
+        .sum::<f64>()
+        / total_weight
+}
+
+/// How much each input counts towards the memory pressure estimate, only the ratios matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryPressureWeights {
+    pub memory: f32,
+    pub swap: f32,
+    pub major_faults: f32,
+    pub dirty: f32,
+}
+
+impl Default for MemoryPressureWeights {
+    fn default() -> Self {
+        Self {
+            memory: 2.0,
+            swap: 1.0,
+            major_faults: 1.0,
+            dirty: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum MemoryPressure {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl MemoryPressure {
+    pub fn name(self) -> &'static str {
+        match self {
+            MemoryPressure::None => "none",
+            MemoryPressure::Low => "low",
+            MemoryPressure::Medium => "medium",
+            MemoryPressure::High => "high",
+            MemoryPressure::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for MemoryPressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// below this much of RAM in use memory doesn't count as pressure at all
+const MEMORY_PRESSURE_FLOOR: f64 = 50.0; // percent
+const MAJOR_FAULT_PRESSURE_CEILING: f64 = 500.0; // per second, system wide
+
+/// Weighs memory that isn't available and swap in use (both in percent), the system-wide major fault rate and how close
+/// dirty memory is to `vm.dirty_ratio` (0 to 1) into one level.
+pub fn compute_memory_pressure(
+    memory_percent: f64,
+    swap_percent: f64,
+    major_faults_per_sec: u64,
+    dirty_fill: f64,
+    weights: &MemoryPressureWeights,
+) -> MemoryPressure {
+    let pressure = weighted_score(&[
+        ((memory_percent - MEMORY_PRESSURE_FLOOR) / (100.0 - MEMORY_PRESSURE_FLOOR), weights.memory),
+        (swap_percent / 100.0, weights.swap),
+        (major_faults_per_sec as f64 / MAJOR_FAULT_PRESSURE_CEILING, weights.major_faults),
+        (dirty_fill, weights.dirty),
+    ]);
+    match pressure {
+        p if p < 0.1 => MemoryPressure::None,
+        p if p < 0.3 => MemoryPressure::Low,
+        p if p < 0.5 => MemoryPressure::Medium,
+        p if p < 0.7 => MemoryPressure::High,
+        _ => MemoryPressure::Critical,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkPort {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub state: String,
+    pub pid: Option<u32>, // None when the owning process is not visible to us
+}
+
+#[derive(Debug, Clone)]
+pub struct UnixSocket {
+    pub pid: u32,
+    pub path: Option<String>, // abstract and unnamed sockets have no path
+    pub state: u8,
+}
+
+impl UnixSocket {
+    pub fn state_name(&self) -> &'static str {
+        // socket_state values from include/uapi/linux/net.h
+        match self.state {
+            1 => "UNCONNECTED",
+            2 => "CONNECTING",
+            3 => "CONNECTED",
+            4 => "DISCONNECTING",
+            _ => "UNKNOWN",
+        }
+    }
+}
+
+/// Share of total cpu time spent in each state since the previous refresh, in percent.
+#[derive(Debug, Clone, Default)]
+pub struct CpuTimeBreakdown {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    pub steal: f32,
+    pub guest: f32,
+}
+
+// raw jiffy counters from the aggregate "cpu" line of /proc/stat
+#[derive(Debug, Clone, Copy, Default)]
+struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+    guest: u64,
+}
+
+impl CpuTimes {
+    // guest time is already included in user, don't count it twice
+    fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+
+    fn breakdown_since(&self, prev: &CpuTimes) -> CpuTimeBreakdown {
+        let total = self.total().saturating_sub(prev.total());
+        if total == 0 {
+            return CpuTimeBreakdown::default();
+        }
+        let pct = |now: u64, before: u64| now.saturating_sub(before) as f32 / total as f32 * 100.0;
+
+        CpuTimeBreakdown {
+            user: pct(self.user, prev.user),
+            nice: pct(self.nice, prev.nice),
+            system: pct(self.system, prev.system),
+            idle: pct(self.idle, prev.idle),
+            iowait: pct(self.iowait, prev.iowait),
+            irq: pct(self.irq, prev.irq),
+            softirq: pct(self.softirq, prev.softirq),
+            steal: pct(self.steal, prev.steal),
+            guest: pct(self.guest, prev.guest),
+        }
+    }
+}
+
+/// Kernel-wide inode and file handle counts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InodeStats {
+    pub total: u64, // inodes currently allocated in the inode cache
+    pub used: u64,
+    pub max_files: u64, // fs.file-max
+    pub open_files: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VmStats {
+    pub minor_faults_per_sec: u64,
+    pub major_faults_per_sec: u64,
+    pub dirty_pages: u64,
+    pub writeback_pages: u64,
+}
+
+impl VmStats {
+    /// Dirty plus in-flight writeback memory, in bytes.
+    pub fn dirty_bytes(&self) -> u64 {
+        (self.dirty_pages + self.writeback_pages) * page_size()
+    }
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 { size as u64 } else { 4096 }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+/// TCP activity from /proc/net/snmp. Everything but `established` is a per-second rate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpStats {
+    pub established: u64,
+    pub active_opens_per_sec: u64,  // outgoing connections
+    pub passive_opens_per_sec: u64, // accepted connections
+    pub attempt_fails_per_sec: u64,
+    pub estab_resets_per_sec: u64,
+    pub retrans_segs_per_sec: u64,
+}
+
+// cumulative counters from the Tcp: lines of /proc/net/snmp, CurrEstab aside
+#[derive(Debug, Clone, Copy, Default)]
+struct TcpCounters {
+    active_opens: u64,
+    passive_opens: u64,
+    attempt_fails: u64,
+    estab_resets: u64,
+    curr_estab: u64,
+    retrans_segs: u64,
+}
+
+// cumulative counters from /proc/vmstat
+#[derive(Debug, Clone, Copy, Default)]
+struct VmCounters {
+    pgfault: u64, // includes major faults
+    pgmajfault: u64,
+    nr_dirty: u64,
+    nr_writeback: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NumaNode {
+    pub id: u32,
+    pub cpus: Vec<u32>,
+    pub memory_total: u64,
+    pub memory_used: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NumaTopology {
+    pub nodes: Vec<NumaNode>,
+}
+
+impl NumaTopology {
+    // a single node (or none on non-linux) means the machine isn't NUMA
+    pub fn is_numa(&self) -> bool {
+        self.nodes.len() > 1
+    }
+
+    pub fn node_of_cpu(&self, cpu: u32) -> Option<u32> {
+        self.nodes
+            .iter()
+            .find(|node| node.cpus.contains(&cpu))
+            .map(|node| node.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount: String,
+    pub total: u64,
+    pub used: u64,
+    pub read_rate: f64, // bytes/sec since the previous refresh
+    pub write_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub rx_bytes: u64, // totals since boot
+    pub tx_bytes: u64,
+    pub rx_rate: f64, // bytes/sec since the previous refresh
+    pub tx_rate: f64,
+}
+
+/// Recent rates of one interface, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkHistory {
+    pub rx: VecDeque<f64>, // bytes/sec
+    pub tx: VecDeque<f64>,
+}
+
+/// Everything a consumer needs from one refresh, sent to `subscribe` receivers.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemSnapshot {
+    pub timestamp: DateTime<Local>,
+    pub cpu_usage: f32,
+    pub per_cpu_usage: Vec<f32>,
+    pub memory_total: u64,
+    pub memory_used: u64,
+    pub disk_read_rate: u64,
+    pub disk_write_rate: u64,
+    pub network_rx_rate: u64,
+    pub network_tx_rate: u64,
+    pub processes: Vec<ProcessInfo>, // sorted by cpu usage
+}
+
+// how far a process has to move from the baseline to show up in a diff
+const DIFF_CPU_THRESHOLD: f32 = 1.0; // percentage points
+const DIFF_MEMORY_THRESHOLD: u64 = 1_000_000; // bytes
+
+impl SystemSnapshot {
+    /// The baseline entry for the same process, a reused pid doesn't count.
+    pub fn baseline_of(&self, process: &ProcessInfo) -> Option<&ProcessInfo> {
+        self.processes
+            .iter()
+            .find(|p| p.pid == process.pid && p.start_time == process.start_time && !p.exited)
+    }
+
+    /// What changed between this snapshot and `current`: processes whose CPU or memory moved
+    /// noticeably, new ones, and at the end those that are gone, marked as exited.
+    pub fn diff(&self, current: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        let mut changed: Vec<ProcessInfo> = current
+            .iter()
+            .filter(|p| !p.exited)
+            .filter(|p| match self.baseline_of(p) {
+                Some(before) => {
+                    (p.cpu_usage - before.cpu_usage).abs() > DIFF_CPU_THRESHOLD
+                        || p.memory.abs_diff(before.memory) > DIFF_MEMORY_THRESHOLD
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+        let removed = self.processes.iter().filter(|before| {
+            !before.exited
+                && !current
+                    .iter()
+                    .any(|p| p.pid == before.pid && p.start_time == before.start_time && !p.exited)
+        });
+        changed.extend(removed.map(|before| ProcessInfo { exited: true, ..before.clone() }));
+        changed
+    }
+}
+
+/// Limits of the cgroup (v2) a process belongs to; `None` means unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct CgroupQuota {
+    pub cpu_max: Option<f32>, // in cores, e.g. 0.5 for "50000 100000"
+    pub memory_max: Option<u64>,
+    pub memory_current: Option<u64>, // charged to the whole cgroup, not just this process
+}
+
+// capability numbers from linux/capability.h, the index is the bit in the masks
+const CAPABILITY_NAMES: [&str; 41] = [
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+pub const CAP_NET_ADMIN: u32 = 12;
+pub const CAP_SYS_ADMIN: u32 = 21;
+
+/// The capability masks from `/proc/<pid>/status`, one bit per capability number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CapabilitySet {
+    pub inheritable: u64,
+    pub permitted: u64,
+    pub effective: u64,
+    pub bounding: u64,
+    pub ambient: u64,
+}
+
+impl CapabilitySet {
+    pub fn has_effective(&self, cap: u32) -> bool {
+        cap < 64 && self.effective & (1 << cap) != 0
+    }
+
+    /// CAP_SYS_ADMIN or CAP_NET_ADMIN, either one is close to root.
+    pub fn is_privileged(&self) -> bool {
+        self.has_effective(CAP_SYS_ADMIN) || self.has_effective(CAP_NET_ADMIN)
+    }
+
+    /// Names of the effective capabilities, bits newer than this table show as `CAP_<n>`.
+    pub fn effective_names(&self) -> Vec<String> {
+        (0..64)
+            .filter(|&cap| self.has_effective(cap))
+            .map(|cap| match CAPABILITY_NAMES.get(cap as usize) {
+                Some(name) => name.to_string(),
+                None => format!("CAP_{}", cap),
+            })
+            .collect()
+    }
+}
+
+/// Syscall filtering mode from the `Seccomp:` line of `/proc/<pid>/status`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeccompMode {
+    Disabled,
+    Strict, // only read, write, _exit and sigreturn
+    Filter, // a BPF filter, what container runtimes and sandboxes install
+}
+
+impl SeccompMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeccompMode::Disabled => "disabled",
+            SeccompMode::Strict => "strict",
+            SeccompMode::Filter => "filter",
+        }
+    }
+}
+
+/// I/O scheduling class and level as set by `ionice`, levels go from 0 (highest) to 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IoPriority {
+    None, // no class set, the kernel derives best-effort from the nice value
+    RealTime(u8),
+    BestEffort(u8),
+    Idle,
+}
+
+// the class sits above the level in the value ioprio_get/ioprio_set use
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+const IOPRIO_LEVELS: u8 = 8;
+
+impl IoPriority {
+    /// Every setting `ionice` can make, in class order.
+    pub fn choices() -> Vec<IoPriority> {
+        let mut choices = vec![IoPriority::None];
+        choices.extend((0..IOPRIO_LEVELS).map(IoPriority::RealTime));
+        choices.extend((0..IOPRIO_LEVELS).map(IoPriority::BestEffort));
+        choices.push(IoPriority::Idle);
+        choices
+    }
+
+    fn from_raw(value: u32) -> Option<IoPriority> {
+        let level = (value & ((1 << IOPRIO_CLASS_SHIFT) - 1)) as u8;
+        match value >> IOPRIO_CLASS_SHIFT {
+            0 => Some(IoPriority::None),
+            1 => Some(IoPriority::RealTime(level)),
+            2 => Some(IoPriority::BestEffort(level)),
+            3 => Some(IoPriority::Idle),
+            _ => None,
+        }
+    }
+
+    fn to_raw(self) -> u32 {
+        let (class, level) = match self {
+            IoPriority::None => (0, 0),
+            IoPriority::RealTime(level) => (1, level),
+            IoPriority::BestEffort(level) => (2, level),
+            IoPriority::Idle => (3, 0),
+        };
+        (class << IOPRIO_CLASS_SHIFT) | u32::from(level)
+    }
+}
+
+impl std::fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoPriority::None => write!(f, "none"),
+            IoPriority::RealTime(level) => write!(f, "real-time/{}", level),
+            IoPriority::BestEffort(level) => write!(f, "best-effort/{}", level),
+            IoPriority::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+/// What `SystemMonitor::kill_process` sends: nix's signals on Unix, sysinfo's elsewhere, where
+/// most of them are refused.
+#[cfg(unix)]
+pub use nix::sys::signal::Signal;
+#[cfg(not(unix))]
+pub use sysinfo::Signal;
+
+/// The signals the kill dialog offers, in its order.
+#[cfg(unix)]
+pub const DIALOG_SIGNALS: [Signal; 5] = [Signal::SIGTERM, Signal::SIGKILL, Signal::SIGSTOP, Signal::SIGCONT, Signal::SIGHUP];
+#[cfg(not(unix))]
+pub const DIALOG_SIGNALS: [Signal; 5] = [Signal::Term, Signal::Kill, Signal::Stop, Signal::Continue, Signal::Hangup];
+
+#[cfg(unix)]
+pub const SIGTERM: Signal = Signal::SIGTERM;
+#[cfg(not(unix))]
+pub const SIGTERM: Signal = Signal::Term;
+#[cfg(unix)]
+pub const SIGKILL: Signal = Signal::SIGKILL;
+#[cfg(not(unix))]
+pub const SIGKILL: Signal = Signal::Kill;
+
+/// The name plus, on Unix, the number, e.g. "SIGTERM (15)".
+pub fn signal_label(signal: Signal) -> String {
+    #[cfg(unix)]
+    return format!("{} ({})", signal, signal as i32);
+    #[cfg(not(unix))]
+    return signal.to_string();
+}
+
+/// Extra per-process information for the detail view, read on demand rather than every refresh.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDetails {
+    pub command_line: Option<String>,
+    pub working_dir: Option<PathBuf>,
+    pub seccomp_mode: Option<SeccompMode>,
+    pub children: usize,
+    pub descendants: usize,
+    pub disk_read_total: u64, // bytes since the process started
+    pub disk_write_total: u64,
+}
+
+// per-process values sysinfo doesn't expose, read from /proc once per refresh
+#[derive(Debug, Clone, Default)]
+struct ProcStats {
+    oom_score: Option<i32>,
+    cgroup: Option<String>,
+    numa_node: Option<u32>,
+    numa_cross_node: bool,
+    cpu_affinity: Option<Vec<u32>>,
+    minflt: u64, // cumulative counters, turned into rates against the previous refresh
+    majflt: u64,
+    minor_faults_per_sec: u64,
+    major_faults_per_sec: u64,
+    state: Option<char>,
+    state_since: Option<Instant>, // first refresh that saw the current state
+    state_changed_at: Option<Instant>,
+    swap_bytes: u64,
+    threads: u32,
+    nice: i32,
+    open_fds: Option<u32>,
+    fd_limit: Option<u64>,
+    capabilities: Option<CapabilitySet>,
+    io_priority: Option<IoPriority>,
+    executable_deleted: bool,
+    gpu_engine_ns: Option<HashMap<String, u64>>, // cumulative busy time per engine, None without a GPU client
+    gpu_usage: Option<f32>,
+    gpu_memory: Option<u64>,
+    hung_alerted: bool, // an alert went out for the current stretch in D
+}
+
+impl ProcStats {
+    fn update_state(&mut self, prev: Option<&ProcStats>, now: Instant) {
+        match prev {
+            Some(prev) if prev.state == self.state => {
+                self.state_since = prev.state_since;
+                self.state_changed_at = prev.state_changed_at;
+            }
+            Some(_) => {
+                self.state_since = Some(now);
+                self.state_changed_at = Some(now);
+            }
+            None => self.state_since = Some(now),
+        }
+    }
+
+    fn is_hung(&self) -> bool {
+        self.state == Some('D') && self.state_since.is_some_and(|since| since.elapsed() > HUNG_STATE_THRESHOLD)
+    }
+
+    fn update_rates(&mut self, prev: &ProcStats, elapsed_secs: f64) {
+        let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / elapsed_secs) as u64;
+        self.minor_faults_per_sec = rate(self.minflt, prev.minflt);
+        self.major_faults_per_sec = rate(self.majflt, prev.majflt);
+        if let (Some(engines), Some(before)) = (&self.gpu_engine_ns, &prev.gpu_engine_ns) {
+            let busiest = engines
+                .iter()
+                .map(|(engine, &busy)| busy.saturating_sub(before.get(engine).copied().unwrap_or(0)))
+                .max()
+                .unwrap_or(0);
+            self.gpu_usage = Some((busiest as f64 / (elapsed_secs * 1e9) * 100.0).min(100.0) as f32);
+        }
+    }
 }
 
 pub struct SystemMonitor {
     system: System,
     cpu_history: Vec<CpuInfo>,
     memory_history: VecDeque<f64>, // memory usage percentage over time
+    swap_history: VecDeque<f64>,   // swap usage percentage over time
+    iowait_history: VecDeque<f32>, // share of CPU time spent waiting on I/O, in percent
+    disk_io_history: VecDeque<f64>, // bytes/sec read plus written, all disks
     max_history_len: usize,
+    collect_ports: bool, // scanning every fd is expensive, only do it while the ports view is open
+    collect_swap: bool,  // same for reading smaps_rollup of every process, only while the swap view is open
+    containers: Vec<ContainerInfo>, // stored by the app's poller, the daemon is never asked from refresh
+    container_error: Option<String>, // why the last poll of the Docker daemon failed
+    ports: Vec<NetworkPort>,
+    unix_sockets: Vec<UnixSocket>,
+    pid_watchers: Vec<(u32, watch::Sender<Option<ProcessInfo>>)>,
+    proc_stats: HashMap<u32, ProcStats>,
+    process_history: HashMap<u32, ProcessHistory>,
+    health_weights: HealthScoreWeights,
+    pressure_weights: MemoryPressureWeights,
+    numa: NumaTopology,
+    cpu_times: Option<CpuTimes>,
+    cpu_breakdown: CpuTimeBreakdown,
+    last_refresh: Instant,
+    refresh_secs: f64, // between the last two refreshes
+    vm_counters: Option<VmCounters>,
+    vm_stats: VmStats,
+    tcp_counters: Option<TcpCounters>,
+    tcp_stats: TcpStats,
+    locked_memory: u64,
+    swappiness: Option<u32>,
+    entropy_avail: Option<u64>,
+    dirty_ratio: Option<u32>,
+    memory_available: Option<u64>, // MemAvailable from /proc/meminfo
+    inode_stats: InodeStats,
+    disks: Vec<DiskInfo>,
+    disk_counters: HashMap<String, (u64, u64)>, // cumulative bytes read/written per block device
+    networks: Vec<NetworkInfo>, // sorted by name
+    network_history: HashMap<String, NetworkHistory>,
+    new_pids: HashSet<u32>,
+    exited_processes: HashMap<u32, (ProcessInfo, Instant)>, // frozen info and when it was last seen
+    first_seen: HashMap<u32, ProcessInfo>, // the row of every live process when it appeared, sysinfo forgets exited ones
+    snapshot_tx: broadcast::Sender<SystemSnapshot>,
+    alerts: VecDeque<Alert>, // the most recent, each consumer reads them from its own cursor
+    alerts_raised: u64,      // ever, the seq of the next alert
+    #[cfg(feature = "gpu")]
+    nvml: Option<nvml::NvmlClients>, // None without an NVIDIA driver
 }
 
 impl SystemMonitor {
@@ -41,10 +1152,16 @@ impl SystemMonitor {
         let cpu_history: Vec<CpuInfo> = system
             .cpus()
             .iter()
-            .map(|cpu| CpuInfo {
+            .enumerate()
+            .map(|(i, cpu)| CpuInfo {
                 name: cpu.name().to_string(),
                 usage: 0.0,
                 history: VecDeque::with_capacity(60),
+                frequency_mhz: cpu.frequency(),
+                max_frequency_mhz: read_max_frequency_mhz(i),
+                efficiency_score: 0.0,
+                governor: read_scaling_governor(i),
+                scaling_range_mhz: read_scaling_range_mhz(i),
             })
             .collect();
 
@@ -52,17 +1169,68 @@ impl SystemMonitor {
             system,
             cpu_history,
             memory_history: VecDeque::with_capacity(60),
+            swap_history: VecDeque::with_capacity(60),
+            iowait_history: VecDeque::with_capacity(60),
+            disk_io_history: VecDeque::with_capacity(60),
             max_history_len: 60,
+            collect_ports: false,
+            collect_swap: false,
+            containers: Vec::new(),
+            container_error: None,
+            ports: Vec::new(),
+            unix_sockets: Vec::new(),
+            pid_watchers: Vec::new(),
+            proc_stats: HashMap::new(),
+            process_history: HashMap::new(),
+            health_weights: HealthScoreWeights::default(),
+            pressure_weights: MemoryPressureWeights::default(),
+            numa: read_numa_topology(),
+            cpu_times: read_cpu_times(),
+            cpu_breakdown: CpuTimeBreakdown::default(),
+            last_refresh: Instant::now(),
+            refresh_secs: 1.0,
+            vm_counters: read_vm_counters(),
+            vm_stats: VmStats::default(),
+            tcp_counters: read_tcp_counters(),
+            tcp_stats: TcpStats::default(),
+            locked_memory: read_locked_memory(),
+            swappiness: read_swappiness(),
+            entropy_avail: read_entropy_avail(),
+            dirty_ratio: read_dirty_ratio(),
+            memory_available: read_meminfo_field("MemAvailable"),
+            inode_stats: read_inode_stats(),
+            disks: Vec::new(),
+            disk_counters: read_disk_counters(),
+            networks: Vec::new(),
+            network_history: HashMap::new(),
+            new_pids: HashSet::new(),
+            exited_processes: HashMap::new(),
+            first_seen: HashMap::new(),
+            snapshot_tx: broadcast::channel(SNAPSHOT_CHANNEL_CAPACITY).0,
+            alerts: VecDeque::new(),
+            alerts_raised: 0,
+            #[cfg(feature = "gpu")]
+            nvml: nvml::NvmlClients::init(),
         }
     }
 
     pub fn refresh(&mut self) {
+        let previous: HashSet<u32> = self.system.processes().keys().map(|pid| pid.as_u32()).collect();
         self.system.refresh_all();
+        let elapsed = self.last_refresh.elapsed().as_secs_f64().max(0.001);
+        self.last_refresh = Instant::now();
+        self.refresh_secs = elapsed;
         
         // update cpu history
         for (i, cpu) in self.system.cpus().iter().enumerate() {
             if let Some(cpu_info) = self.cpu_history.get_mut(i) {
                 cpu_info.usage = cpu.cpu_usage();
+                cpu_info.frequency_mhz = cpu.frequency();
+                cpu_info.efficiency_score =
+                    CpuInfo::efficiency(cpu_info.usage, cpu_info.frequency_mhz, cpu_info.max_frequency_mhz);
+                // both can be changed at any time, e.g. with cpupower
+                cpu_info.governor = read_scaling_governor(i);
+                cpu_info.scaling_range_mhz = read_scaling_range_mhz(i);
                 
                 if cpu_info.history.len() >= self.max_history_len {
                     cpu_info.history.pop_front();
@@ -77,45 +1245,727 @@ impl SystemMonitor {
             self.memory_history.pop_front();
         }
         self.memory_history.push_back(memory_percent);
-    }
 
-    pub fn get_processes(&self, sort_order: &SortOrder) -> Vec<ProcessInfo> {
-        let mut processes: Vec<ProcessInfo> = self
+        let swap_percent = self.get_swap_percent();
+        if self.swap_history.len() >= self.max_history_len {
+            self.swap_history.pop_front();
+        }
+        self.swap_history.push_back(swap_percent);
+
+        self.numa = read_numa_topology();
+
+        let cpu_times = read_cpu_times();
+        if let (Some(now), Some(prev)) = (cpu_times, self.cpu_times) {
+            self.cpu_breakdown = now.breakdown_since(&prev);
+            if self.iowait_history.len() >= self.max_history_len {
+                self.iowait_history.pop_front();
+            }
+            self.iowait_history.push_back(self.cpu_breakdown.iowait);
+        }
+        self.cpu_times = cpu_times;
+
+        let vm_counters = read_vm_counters();
+        if let Some(now) = vm_counters {
+            let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / elapsed) as u64;
+            let prev = self.vm_counters.unwrap_or(now);
+            let minor_now = now.pgfault.saturating_sub(now.pgmajfault);
+            let minor_before = prev.pgfault.saturating_sub(prev.pgmajfault);
+            self.vm_stats = VmStats {
+                minor_faults_per_sec: rate(minor_now, minor_before),
+                major_faults_per_sec: rate(now.pgmajfault, prev.pgmajfault),
+                dirty_pages: now.nr_dirty,
+                writeback_pages: now.nr_writeback,
+            };
+        }
+        self.vm_counters = vm_counters;
+
+        let tcp_counters = read_tcp_counters();
+        if let Some(now) = tcp_counters {
+            let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / elapsed) as u64;
+            let prev = self.tcp_counters.unwrap_or(now);
+            self.tcp_stats = TcpStats {
+                established: now.curr_estab,
+                active_opens_per_sec: rate(now.active_opens, prev.active_opens),
+                passive_opens_per_sec: rate(now.passive_opens, prev.passive_opens),
+                attempt_fails_per_sec: rate(now.attempt_fails, prev.attempt_fails),
+                estab_resets_per_sec: rate(now.estab_resets, prev.estab_resets),
+                retrans_segs_per_sec: rate(now.retrans_segs, prev.retrans_segs),
+            };
+        }
+        self.tcp_counters = tcp_counters;
+        self.locked_memory = read_locked_memory();
+        self.swappiness = read_swappiness();
+        self.entropy_avail = read_entropy_avail();
+        self.dirty_ratio = read_dirty_ratio();
+        self.memory_available = read_meminfo_field("MemAvailable");
+        self.inode_stats = read_inode_stats();
+
+        let disk_counters = read_disk_counters();
+        let rate = |now: u64, before: u64| now.saturating_sub(before) as f64 / elapsed;
+        self.disks = self
+            .system
+            .disks()
+            .iter()
+            .map(|disk| {
+                let name = disk.name().to_string_lossy().into_owned();
+                let device = block_device_name(&name);
+                let (read_rate, write_rate) = match (disk_counters.get(&device), self.disk_counters.get(&device)) {
+                    (Some(now), Some(prev)) => (rate(now.0, prev.0), rate(now.1, prev.1)),
+                    _ => (0.0, 0.0),
+                };
+                DiskInfo {
+                    name,
+                    mount: disk.mount_point().to_string_lossy().into_owned(),
+                    total: disk.total_space(),
+                    used: disk.total_space().saturating_sub(disk.available_space()),
+                    read_rate,
+                    write_rate,
+                }
+            })
+            .collect();
+        self.disk_counters = disk_counters;
+        let disk_io = (self.get_total_disk_read_rate() + self.get_total_disk_write_rate()) as f64;
+        if self.disk_io_history.len() >= self.max_history_len {
+            self.disk_io_history.pop_front();
+        }
+        self.disk_io_history.push_back(disk_io);
+
+        // sysinfo already keeps the per-refresh delta for interfaces
+        self.networks = self
+            .system
+            .networks()
+            .iter()
+            .map(|(name, data)| NetworkInfo {
+                name: name.clone(),
+                rx_bytes: data.total_received(),
+                tx_bytes: data.total_transmitted(),
+                rx_rate: data.received() as f64 / elapsed,
+                tx_rate: data.transmitted() as f64 / elapsed,
+            })
+            .collect();
+        self.networks.sort_by(|a, b| a.name.cmp(&b.name));
+        let networks = &self.networks;
+        self.network_history.retain(|name, _| networks.iter().any(|net| &net.name == name));
+        for net in &self.networks {
+            let history = self.network_history.entry(net.name.clone()).or_default();
+            if history.rx.len() >= self.max_history_len {
+                history.rx.pop_front();
+                history.tx.pop_front();
+            }
+            history.rx.push_back(net.rx_rate);
+            history.tx.push_back(net.tx_rate);
+        }
+
+        let now = Instant::now();
+        let previous_stats = std::mem::take(&mut self.proc_stats);
+        let collect_swap = self.collect_swap;
+        self.proc_stats = self
             .system
             .processes()
-            .values()
-            .map(|proc| ProcessInfo {
-                pid: proc.pid().as_u32(),
-                name: proc.name().to_string(),
-                cpu_usage: proc.cpu_usage(),
-                memory: proc.memory(),
-                memory_percent: (proc.memory() as f32 / self.system.total_memory() as f32) * 100.0,
+            .keys()
+            .map(|pid| {
+                let pid = pid.as_u32();
+                let mut stats = read_proc_stats(pid, &self.numa, collect_swap);
+                let prev = previous_stats.get(&pid);
+                if let Some(prev) = prev {
+                    stats.update_rates(prev, elapsed);
+                }
+                // limits practically never change at runtime, read them once per process
+                stats.fd_limit = match prev {
+                    Some(prev) => prev.fd_limit,
+                    None => read_fd_limit(pid),
+                };
+                stats.update_state(prev, now);
+                stats.hung_alerted = prev.is_some_and(|prev| prev.hung_alerted) && stats.is_hung();
+                (pid, stats)
+            })
+            .collect();
+        #[cfg(feature = "gpu")]
+        self.apply_nvml_clients();
+        let newly_hung: Vec<(u32, f64)> = self
+            .proc_stats
+            .iter_mut()
+            .filter(|(_, stats)| stats.is_hung() && !stats.hung_alerted)
+            .map(|(pid, stats)| {
+                stats.hung_alerted = true;
+                (*pid, stats.state_since.map_or(0.0, |since| since.elapsed().as_secs_f64()))
             })
             .collect();
+        for (pid, secs) in newly_hung {
+            let message = format!("stuck in uninterruptible sleep for {:.0}s", secs);
+            self.raise_alert(AlertKind::Hung, pid, secs, message);
+        }
+
+        self.track_process_changes(&previous, &previous_stats);
+        self.update_process_history();
+
+        if self.collect_ports {
+            self.collect_sockets();
+        }
+
+        self.notify_pid_watchers();
+
+        // building a snapshot clones every process, skip it while nobody is subscribed
+        if self.snapshot_tx.receiver_count() > 0 {
+            let _ = self.snapshot_tx.send(self.snapshot());
+        }
+    }
+
+    /// A receiver that gets a snapshot after every refresh. Receivers more than
+    /// `SNAPSHOT_CHANNEL_CAPACITY` snapshots behind lose the oldest ones and get `RecvError::Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemSnapshot> {
+        self.snapshot_tx.subscribe()
+    }
+
+    /// Records an alert about `pid` (0 for none) for `alerts_since`.
+    pub fn raise_alert(&mut self, kind: AlertKind, pid: u32, value: f64, message: String) {
+        if self.alerts.len() >= MAX_RETAINED_ALERTS {
+            self.alerts.pop_front();
+        }
+        let name = self
+            .system
+            .process(Pid::from_u32(pid))
+            .map(|proc| proc.name().to_string())
+            .unwrap_or_default();
+        self.alerts.push_back(Alert {
+            seq: self.alerts_raised,
+            kind,
+            pid,
+            name,
+            value,
+            message,
+            timestamp: Instant::now(),
+        });
+        self.alerts_raised += 1;
+    }
+
+    /// The alerts raised since `cursor`, oldest first, and moves `cursor` past them. Every
+    /// consumer keeps its own cursor (starting at 0), so reading doesn't take alerts from the others.
+    /// A consumer that falls more than MAX_RETAINED_ALERTS behind misses the oldest.
+    pub fn alerts_since(&self, cursor: &mut u64) -> Vec<Alert> {
+        let alerts = self.alerts.iter().filter(|alert| alert.seq >= *cursor).cloned().collect();
+        *cursor = self.alerts_raised;
+        alerts
+    }
+
+    pub fn snapshot(&self) -> SystemSnapshot {
+        let (network_rx_rate, network_tx_rate) = self.get_total_network_rate();
+        SystemSnapshot {
+            timestamp: Local::now(),
+            cpu_usage: self.get_total_cpu_usage(),
+            per_cpu_usage: self.cpu_history.iter().map(|cpu| cpu.usage).collect(),
+            memory_total: self.get_total_memory(),
+            memory_used: self.get_used_memory(),
+            disk_read_rate: self.get_total_disk_read_rate(),
+            disk_write_rate: self.get_total_disk_write_rate(),
+            network_rx_rate,
+            network_tx_rate,
+            processes: self.get_processes(&SortOrder::Cpu),
+        }
+    }
+
+    // a process can have DRM clients and NVIDIA contexts at once, its busiest engine wins and
+    // the memory adds up
+    #[cfg(feature = "gpu")]
+    fn apply_nvml_clients(&mut self) {
+        let Some(nvml) = &mut self.nvml else {
+            return;
+        };
+        for (pid, client) in nvml.read() {
+            if let Some(stats) = self.proc_stats.get_mut(&pid) {
+                stats.gpu_usage = Some(stats.gpu_usage.unwrap_or(0.0).max(client.usage));
+                stats.gpu_memory = Some(stats.gpu_memory.unwrap_or(0) + client.memory);
+            }
+        }
+    }
+
+    // runs before update_process_history, the history of the exited processes is still there
+    fn track_process_changes(&mut self, previous: &HashSet<u32>, previous_stats: &HashMap<u32, ProcStats>) {
+        let current: HashSet<u32> = self.system.processes().keys().map(|pid| pid.as_u32()).collect();
+        self.new_pids = current.difference(previous).copied().collect();
+
+        // a reused pid is a new process, drop the stale exited entry
+        self.exited_processes
+            .retain(|pid, (_, seen)| !current.contains(pid) && seen.elapsed() < EXITED_PROCESS_TTL);
+        let now = Instant::now();
+        for pid in previous.difference(&current) {
+            let Some(mut info) = self.first_seen.remove(pid) else {
+                continue;
+            };
+            // bring the row up to what the previous refresh saw
+            if let Some(stats) = previous_stats.get(pid) {
+                info.apply_proc_stats(stats);
+            }
+            if let Some(history) = self.process_history.get(pid) {
+                if let (Some(&cpu), Some(&memory)) = (history.cpu.back(), history.memory.back()) {
+                    info.cpu_usage = cpu;
+                    info.memory = memory;
+                    info.memory_percent = memory as f32 / self.system.total_memory() as f32 * 100.0;
+                }
+                info.health_score = compute_health_score(&info, history, &self.health_weights);
+            }
+            info.is_new = false;
+            info.exited = true;
+            self.exited_processes.insert(*pid, (info, now));
+        }
+
+        // built once per process, the first refresh takes the ones that were already running
+        self.first_seen.retain(|pid, _| current.contains(pid));
+        for proc in self.system.processes().values() {
+            let pid = proc.pid().as_u32();
+            if !self.first_seen.contains_key(&pid) {
+                let info = self.process_info(proc);
+                self.first_seen.insert(pid, info);
+            }
+        }
+    }
+
+    fn update_process_history(&mut self) {
+        let processes = self.system.processes();
+        self.process_history.retain(|pid, _| processes.contains_key(&Pid::from_u32(*pid)));
+        for (pid, proc) in processes {
+            self.process_history
+                .entry(pid.as_u32())
+                .or_default()
+                .push(proc.cpu_usage(), proc.memory(), self.max_history_len);
+        }
+    }
+
+    pub fn get_process_history(&self, pid: u32) -> Option<&ProcessHistory> {
+        self.process_history.get(&pid)
+    }
+
+    pub fn set_health_score_weights(&mut self, weights: HealthScoreWeights) {
+        self.health_weights = weights;
+    }
+
+    pub fn set_memory_pressure_weights(&mut self, weights: MemoryPressureWeights) {
+        self.pressure_weights = weights;
+    }
+
+    /// Returns a receiver that gets `Some(info)` after every refresh while `pid` is alive
+    /// and a final `None` once it exits. The sender is dropped after that, so
+    /// `changed()` errors out while `borrow()` keeps returning `None`.
+    pub fn watch_pid(&mut self, pid: u32) -> watch::Receiver<Option<ProcessInfo>> {
+        let (tx, rx) = watch::channel(self.get_process_by_pid(pid));
+        self.pid_watchers.push((pid, tx));
+        rx
+    }
 
-        // sort processes based on the current sort order
-        match sort_order {
-            SortOrder::Cpu => {
-                processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(std::cmp::Ordering::Equal));
+    fn notify_pid_watchers(&mut self) {
+        let watchers = std::mem::take(&mut self.pid_watchers);
+        for (pid, tx) in watchers {
+            let info = self.get_process_by_pid(pid);
+            let exited = info.is_none();
+            // send only fails when every receiver is gone, nobody is listening anymore
+            if tx.send(info).is_ok() && !exited {
+                self.pid_watchers.push((pid, tx));
             }
-            SortOrder::Memory => {
-                processes.sort_by(|a, b| b.memory.cmp(&a.memory));
+        }
+    }
+
+    /// User space processes only, see `get_kernel_threads` for the rest.
+    pub fn get_processes(&self, sort_order: &SortOrder) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|proc| self.process_info(proc))
+            .filter(|info| !info.kernel_thread)
+            .collect();
+        sort_processes(&mut processes, sort_order);
+        processes
+    }
+
+    /// `get_processes` plus the ones that exited in the last few seconds, marked `exited`.
+    pub fn get_processes_with_exited(&self, sort_order: &SortOrder) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|proc| self.process_info(proc))
+            .chain(self.exited_processes.values().map(|(info, _)| info.clone()))
+            .filter(|info| !info.kernel_thread)
+            .collect();
+        sort_processes(&mut processes, sort_order);
+        processes
+    }
+
+    /// Direct children only, a linear scan rather than building the whole tree.
+    pub fn get_process_children(&self, pid: u32) -> Vec<ProcessInfo> {
+        let mut children: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .filter(|proc| proc.parent() == Some(Pid::from_u32(pid)))
+            .map(|proc| self.process_info(proc))
+            .collect();
+        children.sort_by_key(|c| c.pid);
+        children
+    }
+
+    /// Every descendant of `pid`, parents before their children.
+    pub fn get_transitive_children(&self, pid: u32) -> Vec<ProcessInfo> {
+        // one pass over the processes, then the walk only visits descendants
+        let mut children: HashMap<u32, Vec<&Process>> = HashMap::new();
+        for proc in self.system.processes().values() {
+            if let Some(parent) = proc.parent() {
+                children.entry(parent.as_u32()).or_default().push(proc);
             }
-            SortOrder::Pid => {
-                processes.sort_by(|a, b| a.pid.cmp(&b.pid));
+        }
+
+        let mut descendants = Vec::new();
+        let mut seen = HashSet::from([pid]); // pid reuse can make the parent links loop
+        let mut queue = VecDeque::from([pid]);
+        while let Some(parent) = queue.pop_front() {
+            let Some(procs) = children.get_mut(&parent) else {
+                continue;
+            };
+            procs.sort_by_key(|proc| proc.pid());
+            for proc in procs.iter() {
+                let child = proc.pid().as_u32();
+                if seen.insert(child) {
+                    queue.push_back(child);
+                    descendants.push(self.process_info(proc));
+                }
             }
-            SortOrder::Name => {
-                processes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        }
+        descendants
+    }
+
+    pub fn get_process_details(&self, pid: u32) -> ProcessDetails {
+        let disk_usage = self.system.process(Pid::from_u32(pid)).map(|proc| proc.disk_usage());
+        ProcessDetails {
+            command_line: self.get_full_command_line(pid).ok(),
+            working_dir: read_working_dir(pid).or_else(|| {
+                // sysinfo covers the other platforms, it reports an empty path when it can't tell
+                self.system
+                    .process(Pid::from_u32(pid))
+                    .map(|proc| proc.cwd().to_path_buf())
+                    .filter(|cwd| !cwd.as_os_str().is_empty())
+            }),
+            seccomp_mode: read_seccomp_mode(pid),
+            children: self.get_process_children(pid).len(),
+            descendants: self.get_transitive_children(pid).len(),
+            disk_read_total: disk_usage.map_or(0, |usage| usage.total_read_bytes),
+            disk_write_total: disk_usage.map_or(0, |usage| usage.total_written_bytes),
+        }
+    }
+
+    /// The process's argv joined with spaces. Falls back to `/proc/<pid>/cmdline` when sysinfo
+    /// has nothing, then to the executable path for processes that wiped their argv.
+    pub fn get_full_command_line(&self, pid: u32) -> Result<String> {
+        if let Some(proc) = self.system.process(Pid::from_u32(pid)) {
+            if !proc.cmd().is_empty() {
+                return Ok(proc.cmd().join(" "));
             }
         }
+        read_command_line(pid)
+    }
+
+    /// Like `get_processes_with_exited`, reversed when `sort` asks for the other direction.
+    pub fn get_sorted_processes(&self, sort: &SortState) -> Vec<ProcessInfo> {
+        let mut processes = self.get_processes_with_exited(&sort.order);
+        if sort.direction != sort.order.default_direction() {
+            processes.reverse();
+        }
+        processes
+    }
+
+    /// `get_sorted_processes` narrowed down to names matching `filter`.
+    pub fn get_processes_filtered(&self, sort: &SortState, filter: &FilterExpr) -> Vec<ProcessInfo> {
+        let mut processes = self.get_sorted_processes(sort);
+        processes.retain(|p| filter.matches(&p.name));
+        processes
+    }
+
+    /// Processes with pages in swap, biggest first. Unless `set_collect_swap` has every refresh
+    /// read it, the swap usage of each process is read right here.
+    pub fn get_swap_processes(&self) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|proc| {
+                let mut info = self.process_info(proc);
+                if !self.collect_swap {
+                    info.swap_bytes = read_swap_bytes(info.pid);
+                }
+                info
+            })
+            .filter(|info| info.swap_bytes > 0)
+            .collect();
+        processes.sort_by_key(|p| std::cmp::Reverse(p.swap_bytes));
+        processes
+    }
 
+    /// Processes with a negative nice value, highest priority first. Kernel threads are left
+    /// out, plenty of kworkers run at -20.
+    pub fn get_high_priority_processes(&self) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|proc| self.process_info(proc))
+            .filter(|info| info.nice < 0 && !info.kernel_thread)
+            .collect();
+        processes.sort_by_key(|p| (p.nice, p.pid));
         processes
     }
 
+    pub fn get_kernel_threads(&self) -> Vec<ProcessInfo> {
+        let mut threads: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|proc| self.process_info(proc))
+            .filter(|info| info.kernel_thread)
+            .collect();
+        threads.sort_by_key(|t| t.pid);
+        threads
+    }
+
+    pub fn reset_history(&mut self) {
+        for cpu in &mut self.cpu_history {
+            cpu.history.clear();
+        }
+        self.memory_history.clear();
+        self.swap_history.clear();
+        self.iowait_history.clear();
+        self.disk_io_history.clear();
+        self.network_history.clear();
+    }
+
+    /// Whether `pid` was still running at the last refresh.
+    pub fn is_pid_alive(&self, pid: u32) -> bool {
+        self.system.process(Pid::from_u32(pid)).is_some()
+    }
+
+    pub fn get_process_by_pid(&self, pid: u32) -> Option<ProcessInfo> {
+        self.system
+            .process(Pid::from_u32(pid))
+            .map(|proc| self.process_info(proc))
+    }
+
+    /// Sends `signal` to the process. Off Unix sysinfo decides which signals get through, on
+    /// Windows only `SIGKILL`.
+    #[cfg(unix)]
+    pub fn kill_process(&self, pid: u32, signal: Signal) -> Result<()> {
+        // 0 and negative pids address process groups, never send those
+        let raw_pid = libc::pid_t::try_from(pid)
+            .ok()
+            .filter(|pid| *pid > 0)
+            .with_context(|| format!("invalid PID {}", pid))?;
+        nix::sys::signal::kill(nix::unistd::Pid::from_raw(raw_pid), signal)
+            .with_context(|| format!("failed to send {} to PID {}", signal, pid))
+    }
+
+    #[cfg(not(unix))]
+    pub fn kill_process(&self, pid: u32, signal: Signal) -> Result<()> {
+        let proc = self
+            .system
+            .process(Pid::from_u32(pid))
+            .with_context(|| format!("no process with PID {}", pid))?;
+        match proc.kill_with(signal) {
+            Some(true) => Ok(()),
+            Some(false) => anyhow::bail!("failed to send {} to PID {}", signal, pid),
+            None => anyhow::bail!("sending {} is not supported on this platform", signal),
+        }
+    }
+
+    fn process_info(&self, proc: &Process) -> ProcessInfo {
+        let pid = proc.pid().as_u32();
+        let mut info = ProcessInfo::from_with_total(proc, self.system.total_memory());
+        info.is_new = self.new_pids.contains(&pid);
+        // the user list is loaded once at startup, accounts added later keep showing their uid
+        if let Some(user) = proc.user_id().and_then(|uid| self.system.get_user_by_id(uid)) {
+            info.user = user.name().to_string();
+        }
+        // sysinfo counts since the previous refresh, except on a process's first one where it's
+        // the lifetime total, count nothing then
+        if !info.is_new {
+            let usage = proc.disk_usage();
+            info.disk_read_rate = (usage.read_bytes as f64 / self.refresh_secs) as u64;
+            info.disk_write_rate = (usage.written_bytes as f64 / self.refresh_secs) as u64;
+        }
+        if let Some(stats) = self.proc_stats.get(&pid) {
+            info.apply_proc_stats(stats);
+        }
+        if let Some(history) = self.get_process_history(pid) {
+            info.health_score = compute_health_score(&info, history, &self.health_weights);
+        }
+        info
+    }
+
+    /// The cgroup v2 path of a process, e.g. `/user.slice/user-1000.slice/session-2.scope`.
+    pub fn get_process_cgroup(&self, pid: u32) -> Option<String> {
+        match self.proc_stats.get(&pid) {
+            Some(stats) => stats.cgroup.clone(),
+            None => read_cgroup_path(pid),
+        }
+    }
+
+    pub fn get_numa_topology(&self) -> NumaTopology {
+        self.numa.clone()
+    }
+
+    pub fn get_cgroup_quota(&self, pid: u32) -> Option<CgroupQuota> {
+        read_cgroup_quota(pid)
+    }
+
+    /// Pins `pid` to the given cpus with `sched_setaffinity`.
+    pub fn set_cpu_affinity(&self, pid: u32, cpus: &[u32]) -> Result<()> {
+        if cpus.is_empty() {
+            anyhow::bail!("at least one CPU must be selected");
+        }
+        set_cpu_affinity(pid, cpus)
+    }
+
+    /// Like `ionice`; the real-time class and raising another user's process need root.
+    pub fn set_io_priority(&self, pid: u32, priority: IoPriority) -> Result<()> {
+        if let IoPriority::RealTime(level) | IoPriority::BestEffort(level) = priority {
+            if level >= IOPRIO_LEVELS {
+                anyhow::bail!("I/O priority level must be between 0 and {}", IOPRIO_LEVELS - 1);
+            }
+        }
+        set_io_priority(pid, priority)
+    }
+
+    /// Sets the nice value like `renice`; raising a process's priority (lowering nice) needs root.
+    /// Windows has priority classes instead, the value is mapped to the nearest one.
+    pub fn set_nice(&self, pid: u32, nice: i32) -> Result<()> {
+        if !(-20..=19).contains(&nice) {
+            anyhow::bail!("nice value must be between -20 and 19");
+        }
+        set_nice(pid, nice)
+    }
+
+    /// Writes `/proc/<pid>/oom_score_adj`; lowering it below the current value needs root.
+    pub fn set_oom_score_adj(&self, pid: u32, value: i32) -> Result<()> {
+        if !(-1000..=1000).contains(&value) {
+            anyhow::bail!("oom_score_adj must be between -1000 and 1000");
+        }
+        std::fs::write(format!("/proc/{}/oom_score_adj", pid), value.to_string())
+            .with_context(|| format!("failed to set oom_score_adj for PID {} (requires root)", pid))
+    }
+
     pub fn get_cpu_info(&self) -> &Vec<CpuInfo> {
         &self.cpu_history
     }
 
+    /// Minimum and maximum frequency in MHz the scaling governor of `core` may pick, (0, 0)
+    /// where cpufreq isn't available.
+    pub fn get_cpu_frequency_range(&self, core: usize) -> (u64, u64) {
+        self.cpu_history
+            .get(core)
+            .and_then(|cpu| cpu.scaling_range_mhz)
+            .unwrap_or((0, 0))
+    }
+
+    /// `CpuInfo::efficiency_score` of every core, in core order.
+    pub fn get_cpu_efficiency_scores(&self) -> Vec<f32> {
+        self.cpu_history.iter().map(|cpu| cpu.efficiency_score).collect()
+    }
+
+    pub fn get_total_cpu_usage(&self) -> f32 {
+        self.system.global_cpu_info().cpu_usage()
+    }
+
+    pub fn get_cpu_time_breakdown(&self) -> CpuTimeBreakdown {
+        self.cpu_breakdown.clone()
+    }
+
+    /// Share of CPU time spent idle with I/O outstanding since the previous refresh, in percent.
+    /// High values mean processes are stuck waiting on the disks.
+    pub fn get_io_wait_percent(&self) -> f32 {
+        self.cpu_breakdown.iowait
+    }
+
+    pub fn get_io_wait_history(&self) -> &VecDeque<f32> {
+        &self.iowait_history
+    }
+
+    pub fn get_vm_stats(&self) -> VmStats {
+        self.vm_stats.clone()
+    }
+
+    pub fn get_tcp_stats(&self) -> TcpStats {
+        self.tcp_stats
+    }
+
+    /// Memory pinned with `mlock`/`mlockall`, in bytes. It can't be reclaimed or swapped out.
+    pub fn get_locked_memory(&self) -> u64 {
+        self.locked_memory
+    }
+
+    /// `vm.swappiness`, how eagerly the kernel swaps out anonymous memory rather than dropping
+    /// page cache. None where there is no /proc/sys/vm/swappiness.
+    pub fn get_swappiness(&self) -> Option<u32> {
+        self.swappiness
+    }
+
+    /// Bits in the kernel's entropy pool. A low count can stall `getrandom` callers on older
+    /// kernels, e.g. key generation and TLS handshakes. None where the file doesn't exist.
+    pub fn get_entropy_pool_size(&self) -> Option<u64> {
+        self.entropy_avail
+    }
+
+    pub fn get_inode_usage(&self) -> InodeStats {
+        self.inode_stats
+    }
+
+    /// Writes `/proc/sys/vm/swappiness`, which needs root.
+    pub fn set_swappiness(&mut self, value: u32) -> Result<()> {
+        if value > MAX_SWAPPINESS {
+            anyhow::bail!("swappiness must be between 0 and {}", MAX_SWAPPINESS);
+        }
+        std::fs::write(SWAPPINESS_PATH, value.to_string())
+            .context("failed to set swappiness (requires root)")?;
+        self.swappiness = Some(value);
+        Ok(())
+    }
+
+    pub fn get_total_disk_read_rate(&self) -> u64 {
+        self.unique_disks().map(|disk| disk.read_rate).sum::<f64>() as u64
+    }
+
+    pub fn get_total_disk_write_rate(&self) -> u64 {
+        self.unique_disks().map(|disk| disk.write_rate).sum::<f64>() as u64
+    }
+
+    /// Every mounted filesystem, a device mounted in several places is listed once per mount.
+    pub fn get_disk_info(&self) -> &[DiskInfo] {
+        &self.disks
+    }
+
+    pub fn get_disk_io_history(&self) -> &VecDeque<f64> {
+        &self.disk_io_history
+    }
+
+    pub fn get_network_info(&self) -> &[NetworkInfo] {
+        &self.networks
+    }
+
+    pub fn get_network_history(&self, name: &str) -> Option<&NetworkHistory> {
+        self.network_history.get(name)
+    }
+
+    /// System-wide (rx, tx) bytes/sec, loopback excluded since that traffic never leaves the host.
+    pub fn get_total_network_rate(&self) -> (u64, u64) {
+        let (rx, tx) = self
+            .networks
+            .iter()
+            .filter(|net| net.name != "lo")
+            .fold((0.0, 0.0), |(rx, tx), net| (rx + net.rx_rate, tx + net.tx_rate));
+        (rx as u64, tx as u64)
+    }
+
+    // a device mounted in several places (bind mounts, btrfs subvolumes) is listed once per mount
+    fn unique_disks(&self) -> impl Iterator<Item = &DiskInfo> {
+        let mut seen = HashSet::new();
+        self.disks.iter().filter(move |disk| seen.insert(disk.name.as_str()))
+    }
+
     pub fn get_total_memory(&self) -> u64 {
         self.system.total_memory()
     }
@@ -128,10 +1978,110 @@ impl SystemMonitor {
         (self.system.used_memory() as f64 / self.system.total_memory() as f64) * 100.0
     }
 
+    pub fn get_swap_total(&self) -> u64 {
+        self.system.total_swap()
+    }
+
+    pub fn get_swap_used(&self) -> u64 {
+        self.system.used_swap()
+    }
+
+    pub fn get_swap_percent(&self) -> f64 {
+        if self.get_swap_total() == 0 {
+            return 0.0;
+        }
+        (self.get_swap_used() as f64 / self.get_swap_total() as f64) * 100.0
+    }
+
+    /// Memory, swap, major fault and dirty page pressure rolled into one level, weighed by
+    /// `set_memory_pressure_weights`.
+    pub fn estimate_memory_pressure(&self) -> MemoryPressure {
+        // page cache can be dropped at any time, so only count what isn't available. sysinfo
+        // reports free memory as available inside some containers, MemAvailable is right there
+        let available = self.memory_available.unwrap_or_else(|| self.system.available_memory());
+        let total = self.get_total_memory().max(1) as f64;
+        let unavailable_percent = self.get_total_memory().saturating_sub(available) as f64 / total * 100.0;
+        // writers get throttled once dirty memory reaches dirty_ratio percent of RAM
+        let dirty_limit = total * self.dirty_ratio.unwrap_or(DEFAULT_DIRTY_RATIO) as f64 / 100.0;
+        let dirty_fill = self.vm_stats.dirty_bytes() as f64 / dirty_limit.max(1.0);
+        compute_memory_pressure(
+            unavailable_percent,
+            self.get_swap_percent(),
+            self.vm_stats.major_faults_per_sec,
+            dirty_fill,
+            &self.pressure_weights,
+        )
+    }
+
     pub fn get_memory_history(&self) -> &VecDeque<f64> {
         &self.memory_history
     }
 
+    pub fn get_swap_history(&self) -> &VecDeque<f64> {
+        &self.swap_history
+    }
+
+    pub fn set_collect_ports(&mut self, enabled: bool) {
+        self.collect_ports = enabled;
+        if enabled {
+            self.collect_sockets();
+        } else {
+            self.ports.clear();
+            self.unix_sockets.clear();
+        }
+    }
+
+    /// Per-process swap usage (`ProcessInfo::swap_bytes`) is only read on every refresh while
+    /// enabled, it stays 0 otherwise. `get_swap_processes` works either way.
+    pub fn set_collect_swap(&mut self, enabled: bool) {
+        self.collect_swap = enabled;
+        for (pid, stats) in self.proc_stats.iter_mut() {
+            stats.swap_bytes = if enabled { read_swap_bytes(*pid) } else { 0 };
+        }
+    }
+
+    /// Stores the result of a poll of the Docker daemon, see `docker::list_containers`.
+    pub fn set_containers(&mut self, result: Result<Vec<ContainerInfo>>) {
+        match result {
+            Ok(containers) => {
+                self.containers = containers;
+                self.container_error = None;
+            }
+            Err(err) => {
+                self.containers.clear();
+                self.container_error = Some(format!("{:#}", err));
+            }
+        }
+    }
+
+    pub fn clear_containers(&mut self) {
+        self.containers.clear();
+        self.container_error = None;
+    }
+
+    /// Running containers, busiest first, as of the last poll.
+    pub fn get_container_list(&self) -> &[ContainerInfo] {
+        &self.containers
+    }
+
+    pub fn get_container_error(&self) -> Option<&str> {
+        self.container_error.as_deref()
+    }
+
+    fn collect_sockets(&mut self) {
+        let owners = socket_inode_owners();
+        self.ports = read_listening_ports(&owners);
+        self.unix_sockets = read_unix_sockets(&owners);
+    }
+
+    pub fn get_listening_ports(&self) -> &[NetworkPort] {
+        &self.ports
+    }
+
+    pub fn get_unix_sockets(&self) -> &[UnixSocket] {
+        &self.unix_sockets
+    }
+
     pub fn get_system_info(&self) -> SystemInfo {
         SystemInfo {
             hostname: self.system.host_name().unwrap_or_else(|| "unknown".to_string()),
@@ -148,4 +2098,1281 @@ pub struct SystemInfo {
     pub kernel_version: String,
     pub os_version: String,
     pub uptime: u64,
-}
\ No newline at end of file
+}
+
+// snapshots a subscriber can fall behind by before the oldest are dropped
+pub const SNAPSHOT_CHANNEL_CAPACITY: usize = 10;
+
+/// Listening ports and unix sockets are read from /proc, other platforms list none.
+pub const SOCKETS_SUPPORTED: bool = cfg!(target_os = "linux");
+
+// alerts kept for consumers that haven't read them yet, the oldest go first
+const MAX_RETAINED_ALERTS: usize = 100;
+
+/// What raised an alert.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertKind {
+    Hung, // a process stayed in uninterruptible sleep past HUNG_STATE_THRESHOLD
+    #[cfg(feature = "lua")]
+    Script, // `alert()` in the Lua script
+    #[cfg(feature = "rhai")]
+    Rule, // `alert()` in the Rhai rules
+}
+
+impl AlertKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlertKind::Hung => "hung",
+            #[cfg(feature = "lua")]
+            AlertKind::Script => "script",
+            #[cfg(feature = "rhai")]
+            AlertKind::Rule => "rule",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub seq: u64, // raised in order, counting from 0
+    pub kind: AlertKind,
+    pub pid: u32,     // 0 if the alert isn't about one process
+    pub name: String, // of pid when the alert was raised, empty if it had already exited
+    pub value: f64,   // whatever the raiser measured, e.g. the cpu usage that crossed a limit
+    pub message: String,
+    pub timestamp: Instant,
+}
+
+// a process stuck in D this long usually means a hung driver or unreachable NFS server
+const HUNG_STATE_THRESHOLD: Duration = Duration::from_secs(30);
+
+// in the column's default direction, see `SortOrder::default_direction`
+fn sort_processes(processes: &mut [ProcessInfo], sort_order: &SortOrder) {
+    match sort_order {
+        SortOrder::Cpu => {
+            processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(std::cmp::Ordering::Equal));
+        }
+        SortOrder::Memory => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.memory));
+        }
+        SortOrder::Pid => {
+            processes.sort_by_key(|p| p.pid);
+        }
+        SortOrder::Name => {
+            processes.sort_by_cached_key(|p| p.name.to_lowercase());
+        }
+        SortOrder::User => {
+            processes.sort_by_cached_key(|p| p.user.to_lowercase());
+        }
+        SortOrder::StartTime => {
+            // oldest first
+            processes.sort_by_key(|p| p.start_time);
+        }
+        SortOrder::Nice => {
+            // highest priority first
+            processes.sort_by_key(|p| p.nice);
+        }
+        SortOrder::OomScore => {
+            // highest score first, that's the next victim of the OOM killer
+            processes.sort_by_key(|p| std::cmp::Reverse(p.oom_score));
+        }
+        SortOrder::MajorFaults => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.major_faults_per_sec));
+        }
+        SortOrder::MemoryEfficiency => {
+            // least efficient first
+            processes.sort_by(|a, b| a.virtual_memory_ratio().total_cmp(&b.virtual_memory_ratio()));
+        }
+        SortOrder::HealthScore => {
+            // least healthy first
+            processes.sort_by_key(|p| p.health_score);
+        }
+        SortOrder::Threads => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.threads));
+        }
+        SortOrder::DiskRead => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.disk_read_rate));
+        }
+        SortOrder::DiskWrite => {
+            processes.sort_by_key(|p| std::cmp::Reverse(p.disk_write_rate));
+        }
+        SortOrder::GpuUsage => {
+            // processes without a GPU client last
+            processes.sort_by(|a, b| b.gpu_usage.unwrap_or(-1.0).total_cmp(&a.gpu_usage.unwrap_or(-1.0)));
+        }
+    }
+}
+
+// how long an exited process stays in the list with its last known data
+const EXITED_PROCESS_TTL: Duration = Duration::from_secs(3);
+
+// kthreadd is pid 2 and every kernel thread is its child. An empty command line and no
+// memory isn't enough, zombies look like that too and belong in the main table
+#[cfg(target_os = "linux")]
+fn is_kernel_thread(proc: &Process) -> bool {
+    if proc.status() == sysinfo::ProcessStatus::Zombie {
+        return false;
+    }
+    let pid = proc.pid().as_u32();
+    pid == 2 || proc.parent().is_some_and(|ppid| ppid.as_u32() == 2)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_kernel_thread(_proc: &Process) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc_stats(pid: u32, numa: &NumaTopology, read_swap: bool) -> ProcStats {
+    let read = |file: &str| std::fs::read_to_string(format!("/proc/{}/{}", pid, file)).ok();
+
+    let mut stats = ProcStats {
+        oom_score: read("oom_score").and_then(|s| s.trim().parse().ok()),
+        cgroup: read_cgroup_path(pid),
+        cpu_affinity: get_cpu_affinity(pid),
+        swap_bytes: if read_swap { read_swap_bytes(pid) } else { 0 },
+        open_fds: std::fs::read_dir(format!("/proc/{}/fd", pid))
+            .ok()
+            .map(|fds| fds.count() as u32),
+        // the kernel appends " (deleted)" to the link once the file is unlinked
+        executable_deleted: std::fs::read_link(format!("/proc/{}/exe", pid))
+            .is_ok_and(|exe| exe.to_string_lossy().ends_with(" (deleted)")),
+        ..Default::default()
+    };
+    if let Some((engine_ns, vram)) = read_gpu_clients(pid) {
+        stats.gpu_engine_ns = Some(engine_ns);
+        stats.gpu_memory = Some(vram);
+    }
+
+    let stat = read("stat");
+    let stat = stat.as_deref().and_then(stat_fields).unwrap_or_default();
+    let stat_field = |index: usize| stat.get(index).and_then(|value| value.parse::<u64>().ok());
+    stats.state = stat.get(STAT_STATE).and_then(|state| state.chars().next());
+    stats.minflt = stat_field(STAT_MINFLT).unwrap_or(0);
+    stats.majflt = stat_field(STAT_MAJFLT).unwrap_or(0);
+    stats.threads = stat_field(STAT_NUM_THREADS).unwrap_or(0) as u32;
+    stats.nice = stat.get(STAT_NICE).and_then(|nice| nice.parse().ok()).unwrap_or(0);
+    stats.capabilities = read("status").and_then(|status| parse_capabilities(&status));
+    stats.io_priority = get_io_priority(pid);
+
+    // numa_maps walks every mapping of the process, only worth it on real NUMA machines
+    if numa.is_numa() {
+        stats.numa_node = read("numa_maps").and_then(|maps| dominant_numa_node(&maps));
+        let running_node = stat_field(STAT_PROCESSOR).and_then(|cpu| numa.node_of_cpu(cpu as u32));
+        stats.numa_cross_node = matches!(
+            (stats.numa_node, running_node),
+            (Some(memory), Some(running)) if memory != running
+        );
+    }
+
+    stats
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_proc_stats(_pid: u32, _numa: &NumaTopology, _read_swap: bool) -> ProcStats {
+    ProcStats::default()
+}
+
+#[cfg(target_os = "linux")]
+fn get_cpu_affinity(pid: u32) -> Option<Vec<u32>> {
+    // SAFETY: cpu_set_t is plain data, zeroed is a valid empty set, and the
+    // kernel writes at most size_of::<cpu_set_t>() bytes into it.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return None;
+        }
+        Some(
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                .map(|cpu| cpu as u32)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_cpu_affinity(pid: u32, cpus: &[u32]) -> Result<()> {
+    // SAFETY: same as get_cpu_affinity, CPU_SET ignores indexes past CPU_SETSIZE
+    let result = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            libc::CPU_SET(cpu as usize, &mut set);
+        }
+        libc::sched_setaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("failed to set CPU affinity for PID {}", pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_cpu_affinity(_pid: u32, _cpus: &[u32]) -> Result<()> {
+    anyhow::bail!("setting CPU affinity is only supported on Linux")
+}
+
+#[cfg(unix)]
+fn set_nice(pid: u32, nice: i32) -> Result<()> {
+    // SAFETY: setpriority only takes integers and touches no memory of ours
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("failed to set the nice value of PID {}", pid));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_nice(pid: u32, nice: i32) -> Result<()> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{OpenProcess, SetPriorityClass};
+    use winapi::um::winbase::{
+        ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS,
+    };
+    use winapi::um::winnt::PROCESS_SET_INFORMATION;
+
+    // realtime is left out, it can starve the system
+    let class = match nice {
+        -20..=-11 => HIGH_PRIORITY_CLASS,
+        -10..=-1 => ABOVE_NORMAL_PRIORITY_CLASS,
+        0 => NORMAL_PRIORITY_CLASS,
+        1..=10 => BELOW_NORMAL_PRIORITY_CLASS,
+        _ => IDLE_PRIORITY_CLASS,
+    };
+    // SAFETY: the handle is checked before use and closed on every path after OpenProcess
+    let error = unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION, 0, pid);
+        if handle.is_null() {
+            Some(std::io::Error::last_os_error())
+        } else {
+            let error = (SetPriorityClass(handle, class) == 0).then(std::io::Error::last_os_error);
+            CloseHandle(handle);
+            error
+        }
+    };
+    match error {
+        Some(err) => Err(err).with_context(|| format!("failed to set the priority class of PID {}", pid)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn set_nice(_pid: u32, _nice: i32) -> Result<()> {
+    anyhow::bail!("changing the nice value is not supported on this platform")
+}
+
+// libc has no wrappers for ioprio_get/ioprio_set, only the syscall numbers
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+
+#[cfg(target_os = "linux")]
+fn get_io_priority(pid: u32) -> Option<IoPriority> {
+    // SAFETY: ioprio_get only takes integers and touches no memory of ours
+    let value = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_int) };
+    if value < 0 {
+        return None;
+    }
+    IoPriority::from_raw(value as u32)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_io_priority(_pid: u32) -> Option<IoPriority> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn set_io_priority(pid: u32, priority: IoPriority) -> Result<()> {
+    // SAFETY: as for ioprio_get
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            pid as libc::c_int,
+            priority.to_raw() as libc::c_int,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("failed to set I/O priority for PID {}", pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_io_priority(_pid: u32, _priority: IoPriority) -> Result<()> {
+    anyhow::bail!("setting I/O priority is only supported on Linux")
+}
+
+#[cfg(target_os = "linux")]
+fn read_command_line(pid: u32) -> Result<String> {
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid))
+        .with_context(|| format!("failed to read command line of PID {}", pid))?;
+    // argv is NUL separated, with a trailing NUL
+    let args: Vec<_> = cmdline
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(String::from_utf8_lossy)
+        .collect();
+    if !args.is_empty() {
+        return Ok(args.join(" "));
+    }
+    let exe = std::fs::read_link(format!("/proc/{}/exe", pid))
+        .with_context(|| format!("PID {} has no command line or executable", pid))?;
+    Ok(exe.display().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_command_line(pid: u32) -> Result<String> {
+    anyhow::bail!("no command line for PID {}", pid)
+}
+
+// the CapInh/CapPrm/CapEff/CapBnd/CapAmb hex masks of /proc/<pid>/status
+#[cfg(target_os = "linux")]
+fn parse_capabilities(status: &str) -> Option<CapabilitySet> {
+    let mask = |field: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(field))
+            .and_then(|value| u64::from_str_radix(value.trim(), 16).ok())
+    };
+    Some(CapabilitySet {
+        inheritable: mask("CapInh:")?,
+        permitted: mask("CapPrm:")?,
+        effective: mask("CapEff:")?,
+        bounding: mask("CapBnd:")?,
+        ambient: mask("CapAmb:").unwrap_or(0), // only since linux 4.3
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn read_seccomp_mode(pid: u32) -> Option<SeccompMode> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let line = status.lines().find_map(|line| line.strip_prefix("Seccomp:"))?;
+    match line.trim() {
+        "0" => Some(SeccompMode::Disabled),
+        "1" => Some(SeccompMode::Strict),
+        "2" => Some(SeccompMode::Filter),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_seccomp_mode(_pid: u32) -> Option<SeccompMode> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_working_dir(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_working_dir(_pid: u32) -> Option<PathBuf> {
+    None
+}
+
+// indexes into stat_fields() output, i.e. field number from proc(5) minus 3
+#[cfg(target_os = "linux")]
+const STAT_STATE: usize = 0;
+#[cfg(target_os = "linux")]
+const STAT_MINFLT: usize = 7;
+#[cfg(target_os = "linux")]
+const STAT_MAJFLT: usize = 9;
+#[cfg(target_os = "linux")]
+const STAT_NICE: usize = 16;
+#[cfg(target_os = "linux")]
+const STAT_NUM_THREADS: usize = 17;
+#[cfg(target_os = "linux")]
+const STAT_PROCESSOR: usize = 36;
+
+// soft limit from the "Max open files  1024  524288  files" line of /proc/<pid>/limits
+#[cfg(target_os = "linux")]
+fn read_fd_limit(pid: u32) -> Option<u64> {
+    let limits = std::fs::read_to_string(format!("/proc/{}/limits", pid)).ok()?;
+    let line = limits.lines().find(|line| line.starts_with("Max open files"))?;
+    line["Max open files".len()..].split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_fd_limit(_pid: u32) -> Option<u64> {
+    None
+}
+
+// Busy time per engine and VRAM of the process's DRM clients, from the fdinfo of its
+// /dev/dri fds. amdgpu, i915, xe and most embedded drivers publish these; NVIDIA's proprietary
+// driver doesn't, its processes are covered by NVML instead.
+#[cfg(all(target_os = "linux", feature = "gpu"))]
+fn read_gpu_clients(pid: u32) -> Option<(HashMap<String, u64>, u64)> {
+    let mut seen_clients = HashSet::new();
+    let mut engine_ns: HashMap<String, u64> = HashMap::new();
+    let mut vram = 0;
+    for fd in std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?.flatten() {
+        if !std::fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri/")) {
+            continue;
+        }
+        let Ok(fdinfo) = std::fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd.file_name().to_string_lossy()))
+        else {
+            continue;
+        };
+        let Some(client) = parse_drm_fdinfo(&fdinfo) else {
+            continue;
+        };
+        // every fd sharing a client reports the same counters
+        if !seen_clients.insert(client.id) {
+            continue;
+        }
+        for (engine, busy) in client.engine_ns {
+            *engine_ns.entry(engine).or_default() += busy;
+        }
+        vram += client.vram;
+    }
+    (!seen_clients.is_empty()).then_some((engine_ns, vram))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "gpu")))]
+fn read_gpu_clients(_pid: u32) -> Option<(HashMap<String, u64>, u64)> {
+    None
+}
+
+#[cfg(all(target_os = "linux", feature = "gpu"))]
+struct DrmClient {
+    id: u64,
+    engine_ns: Vec<(String, u64)>,
+    vram: u64,
+}
+
+// the "drm-client-id", "drm-engine-<name>: <ns> ns" and "drm-memory-vram: <n> KiB" keys of
+// Documentation/gpu/drm-usage-stats.rst. Newer kernels also print drm-total-vram, the same value
+#[cfg(all(target_os = "linux", feature = "gpu"))]
+fn parse_drm_fdinfo(fdinfo: &str) -> Option<DrmClient> {
+    let mut client = DrmClient { id: 0, engine_ns: Vec::new(), vram: 0 };
+    let mut has_id = false;
+    for line in fdinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if key == "drm-client-id" {
+            client.id = value.parse().ok()?;
+            has_id = true;
+        } else if key == "drm-memory-vram" || key == "drm-total-vram" {
+            let (amount, unit) = value.split_once(' ').unwrap_or((value, ""));
+            let multiplier = match unit {
+                "KiB" => 1024,
+                "MiB" => 1024 * 1024,
+                "GiB" => 1024 * 1024 * 1024,
+                _ => 1,
+            };
+            client.vram = client.vram.max(amount.parse::<u64>().unwrap_or(0) * multiplier);
+        } else if let Some(engine) = key.strip_prefix("drm-engine-").filter(|engine| !engine.starts_with("capacity-")) {
+            if let Some(busy) = value.strip_suffix(" ns").and_then(|ns| ns.parse().ok()) {
+                client.engine_ns.push((engine.to_string(), busy));
+            }
+        }
+    }
+    has_id.then_some(client)
+}
+
+// smaps_rollup sums every mapping of the process, too slow to read for all of them each refresh
+#[cfg(target_os = "linux")]
+fn read_swap_bytes(pid: u32) -> u64 {
+    std::fs::read_to_string(format!("/proc/{}/smaps_rollup", pid))
+        .ok()
+        .and_then(|smaps| smaps_swap_bytes(&smaps))
+        .unwrap_or(0)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_swap_bytes(_pid: u32) -> u64 {
+    0
+}
+
+// the "Swap:  1234 kB" line of smaps_rollup
+#[cfg(target_os = "linux")]
+fn smaps_swap_bytes(smaps: &str) -> Option<u64> {
+    let line = smaps.lines().find(|line| line.starts_with("Swap:"))?;
+    let kb = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kb * 1024)
+}
+
+// /proc/<pid>/stat fields after the "(comm)" entry, which may itself contain spaces
+#[cfg(target_os = "linux")]
+fn stat_fields(stat: &str) -> Option<Vec<&str>> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    Some(rest.split_whitespace().collect())
+}
+
+// sums the per-node page counts ("N0=12 N1=3") over all mappings and picks the largest
+#[cfg(target_os = "linux")]
+fn dominant_numa_node(numa_maps: &str) -> Option<u32> {
+    let mut pages: HashMap<u32, u64> = HashMap::new();
+    for token in numa_maps.split_whitespace() {
+        if let Some((node, count)) = token.strip_prefix('N').and_then(|t| t.split_once('=')) {
+            if let (Ok(node), Ok(count)) = (node.parse::<u32>(), count.parse::<u64>()) {
+                *pages.entry(node).or_default() += count;
+            }
+        }
+    }
+    pages.into_iter().max_by_key(|(_, count)| *count).map(|(node, _)| node)
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let values: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .map(|v| v.parse().unwrap_or(0))
+        .collect();
+    let field = |i: usize| values.get(i).copied().unwrap_or(0);
+
+    Some(CpuTimes {
+        user: field(0),
+        nice: field(1),
+        system: field(2),
+        idle: field(3),
+        iowait: field(4),
+        irq: field(5),
+        softirq: field(6),
+        steal: field(7),
+        guest: field(8),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cpu_times() -> Option<CpuTimes> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_vm_counters() -> Option<VmCounters> {
+    let vmstat = std::fs::read_to_string("/proc/vmstat").ok()?;
+    let mut counters = VmCounters::default();
+    for line in vmstat.lines() {
+        let Some((key, value)) = line.split_once(' ') else {
+            continue;
+        };
+        let value = value.trim().parse::<u64>().unwrap_or(0);
+        match key {
+            "pgfault" => counters.pgfault = value,
+            "pgmajfault" => counters.pgmajfault = value,
+            "nr_dirty" => counters.nr_dirty = value,
+            "nr_writeback" => counters.nr_writeback = value,
+            _ => {}
+        }
+    }
+    Some(counters)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_vm_counters() -> Option<VmCounters> {
+    None
+}
+
+// /proc/net/snmp has a "Tcp:" line of field names followed by one of values
+#[cfg(target_os = "linux")]
+fn read_tcp_counters() -> Option<TcpCounters> {
+    let snmp = std::fs::read_to_string("/proc/net/snmp").ok()?;
+    let mut tcp_lines = snmp.lines().filter_map(|line| line.strip_prefix("Tcp:"));
+    let (names, values) = (tcp_lines.next()?, tcp_lines.next()?);
+    let mut counters = TcpCounters::default();
+    for (name, value) in names.split_whitespace().zip(values.split_whitespace()) {
+        let value = value.parse::<u64>().unwrap_or(0);
+        match name {
+            "ActiveOpens" => counters.active_opens = value,
+            "PassiveOpens" => counters.passive_opens = value,
+            "AttemptFails" => counters.attempt_fails = value,
+            "EstabResets" => counters.estab_resets = value,
+            "CurrEstab" => counters.curr_estab = value,
+            "RetransSegs" => counters.retrans_segs = value,
+            _ => {}
+        }
+    }
+    Some(counters)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_tcp_counters() -> Option<TcpCounters> {
+    None
+}
+
+// a /proc/meminfo field in bytes, the file reports them in kB
+#[cfg(target_os = "linux")]
+fn read_meminfo_field(name: &str) -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_meminfo_field(_name: &str) -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpufreq(cpu: usize, file: &str) -> Option<String> {
+    let path = format!("/sys/devices/system/cpu/cpu{}/cpufreq/{}", cpu, file);
+    Some(std::fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cpufreq(_cpu: usize, _file: &str) -> Option<String> {
+    None
+}
+
+// cpufreq reports frequencies in kHz
+fn read_cpufreq_mhz(cpu: usize, file: &str) -> Option<u64> {
+    read_cpufreq(cpu, file)?.parse::<u64>().ok().map(|khz| khz / 1000)
+}
+
+// the hardware limit, boost included
+fn read_max_frequency_mhz(cpu: usize) -> Option<u64> {
+    read_cpufreq_mhz(cpu, "cpuinfo_max_freq")
+}
+
+fn read_scaling_range_mhz(cpu: usize) -> Option<(u64, u64)> {
+    Some((read_cpufreq_mhz(cpu, "scaling_min_freq")?, read_cpufreq_mhz(cpu, "scaling_max_freq")?))
+}
+
+fn read_scaling_governor(cpu: usize) -> Option<String> {
+    read_cpufreq(cpu, "scaling_governor")
+}
+
+fn read_locked_memory() -> u64 {
+    read_meminfo_field("Mlocked").unwrap_or(0)
+}
+
+// inode-state starts with "nr_inodes nr_free_inodes", file-nr is "allocated free max"
+#[cfg(target_os = "linux")]
+fn read_inode_stats() -> InodeStats {
+    let read_numbers = |path: &str| -> Vec<u64> {
+        std::fs::read_to_string(path)
+            .map(|text| text.split_whitespace().map(|n| n.parse().unwrap_or(0)).collect())
+            .unwrap_or_default()
+    };
+    let inodes = read_numbers("/proc/sys/fs/inode-state");
+    let files = read_numbers("/proc/sys/fs/file-nr");
+    let field = |values: &[u64], i: usize| values.get(i).copied().unwrap_or(0);
+
+    InodeStats {
+        total: field(&inodes, 0),
+        used: field(&inodes, 0).saturating_sub(field(&inodes, 1)),
+        max_files: field(&files, 2),
+        open_files: field(&files, 0).saturating_sub(field(&files, 1)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_inode_stats() -> InodeStats {
+    InodeStats::default()
+}
+
+const SWAPPINESS_PATH: &str = "/proc/sys/vm/swappiness";
+// 100 was the ceiling before linux 5.8
+pub const MAX_SWAPPINESS: u32 = 200;
+
+fn read_swappiness() -> Option<u32> {
+    std::fs::read_to_string(SWAPPINESS_PATH).ok()?.trim().parse().ok()
+}
+
+fn read_entropy_avail() -> Option<u64> {
+    std::fs::read_to_string("/proc/sys/kernel/random/entropy_avail").ok()?.trim().parse().ok()
+}
+
+// the kernel default for vm.dirty_ratio
+const DEFAULT_DIRTY_RATIO: u32 = 20;
+
+fn read_dirty_ratio() -> Option<u32> {
+    std::fs::read_to_string("/proc/sys/vm/dirty_ratio").ok()?.trim().parse().ok()
+}
+
+// /proc/diskstats counts in 512-byte sectors regardless of the device's sector size
+#[cfg(target_os = "linux")]
+const DISKSTATS_SECTOR_SIZE: u64 = 512;
+
+#[cfg(target_os = "linux")]
+fn read_disk_counters() -> HashMap<String, (u64, u64)> {
+    let Ok(diskstats) = std::fs::read_to_string("/proc/diskstats") else {
+        return HashMap::new();
+    };
+    diskstats
+        .lines()
+        .filter_map(|line| {
+            // major minor name reads merged sectors_read ms writes merged sectors_written ...
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let sectors_read = fields.get(5)?.parse::<u64>().ok()?;
+            let sectors_written = fields.get(9)?.parse::<u64>().ok()?;
+            Some((
+                fields[2].to_string(),
+                (sectors_read * DISKSTATS_SECTOR_SIZE, sectors_written * DISKSTATS_SECTOR_SIZE),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_disk_counters() -> HashMap<String, (u64, u64)> {
+    HashMap::new()
+}
+
+// diskstats uses kernel names, so /dev/mapper/root has to be resolved to dm-0
+#[cfg(target_os = "linux")]
+fn block_device_name(name: &str) -> String {
+    let path = std::fs::canonicalize(name).unwrap_or_else(|_| name.into());
+    path.file_name()
+        .map(|file| file.to_string_lossy().into_owned())
+        .unwrap_or_else(|| name.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn block_device_name(name: &str) -> String {
+    name.to_string()
+}
+
+// parses kernel cpu lists like "0-3,8,10-11"
+#[cfg(target_os = "linux")]
+fn parse_cpu_list(list: &str) -> Vec<u32> {
+    list.trim()
+        .split(',')
+        .filter(|part| !part.is_empty())
+        .flat_map(|part| match part.split_once('-') {
+            Some((start, end)) => match (start.parse::<u32>(), end.parse::<u32>()) {
+                (Ok(start), Ok(end)) => (start..=end).collect(),
+                _ => Vec::new(),
+            },
+            None => part.parse::<u32>().into_iter().collect(),
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn read_numa_topology() -> NumaTopology {
+    let Ok(entries) = std::fs::read_dir("/sys/devices/system/node") else {
+        return NumaTopology::default();
+    };
+
+    let mut nodes: Vec<NumaNode> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.strip_prefix("node")?.parse::<u32>().ok()?;
+            let cpus = std::fs::read_to_string(entry.path().join("cpulist"))
+                .map(|list| parse_cpu_list(&list))
+                .unwrap_or_default();
+
+            // lines look like "Node 0 MemTotal:        5996280 kB"
+            let meminfo = std::fs::read_to_string(entry.path().join("meminfo")).unwrap_or_default();
+            let field = |name: &str| {
+                meminfo
+                    .lines()
+                    .find_map(|line| line.split_once(name).map(|(_, rest)| rest))
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .and_then(|kb| kb.parse::<u64>().ok())
+                    .map(|kb| kb * 1024)
+                    .unwrap_or(0)
+            };
+
+            Some(NumaNode {
+                id,
+                cpus,
+                memory_total: field("MemTotal:"),
+                memory_used: field("MemUsed:"),
+            })
+        })
+        .collect();
+
+    nodes.sort_by_key(|node| node.id);
+    NumaTopology { nodes }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_numa_topology() -> NumaTopology {
+    NumaTopology::default()
+}
+
+// the unified (v2) hierarchy entry from /proc/<pid>/cgroup, e.g. "/user.slice/user-1000.slice"
+#[cfg(target_os = "linux")]
+fn read_cgroup_path(pid: u32) -> Option<String> {
+    let contents = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn read_cgroup_quota(pid: u32) -> Option<CgroupQuota> {
+    let dir = format!("/sys/fs/cgroup{}", read_cgroup_path(pid)?);
+    let read = |file: &str| std::fs::read_to_string(format!("{}/{}", dir, file)).ok();
+
+    // cpu.max is "<quota> <period>" or "max <period>"
+    let cpu_max = read("cpu.max").and_then(|contents| {
+        let mut fields = contents.split_whitespace();
+        let quota = fields.next()?.parse::<f32>().ok()?;
+        let period = fields.next()?.parse::<f32>().ok()?;
+        (period > 0.0).then_some(quota / period)
+    });
+    let memory_max = read("memory.max").and_then(|contents| contents.trim().parse::<u64>().ok());
+    let memory_current = read("memory.current").and_then(|contents| contents.trim().parse::<u64>().ok());
+
+    Some(CgroupQuota {
+        cpu_max,
+        memory_max,
+        memory_current,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cgroup_path(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cgroup_quota(_pid: u32) -> Option<CgroupQuota> {
+    None
+}
+
+// maps socket inodes to the pid holding them open by walking /proc/<pid>/fd
+#[cfg(target_os = "linux")]
+fn socket_inode_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        // fds of other users' processes are unreadable without root, skip them quietly
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if let Ok(target) = std::fs::read_link(fd.path()) {
+                let target = target.to_string_lossy();
+                if let Some(inode) = target
+                    .strip_prefix("socket:[")
+                    .and_then(|rest| rest.strip_suffix(']'))
+                    .and_then(|inode| inode.parse::<u64>().ok())
+                {
+                    owners.entry(inode).or_insert(pid);
+                }
+            }
+        }
+    }
+
+    owners
+}
+
+#[cfg(target_os = "linux")]
+fn read_listening_ports(owners: &HashMap<u64, u32>) -> Vec<NetworkPort> {
+    let mut ports = Vec::new();
+
+    for (path, protocol) in [
+        ("/proc/net/tcp", Protocol::Tcp),
+        ("/proc/net/tcp6", Protocol::Tcp6),
+        ("/proc/net/udp", Protocol::Udp),
+        ("/proc/net/udp6", Protocol::Udp6),
+    ] {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            continue;
+        };
+
+        // skip the header line; columns are: sl local_address rem_address st ... uid timeout inode
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                continue;
+            }
+
+            let state = match (protocol, fields[3]) {
+                (Protocol::Tcp | Protocol::Tcp6, "0A") => "LISTEN",
+                (Protocol::Udp | Protocol::Udp6, "07") => "UNCONN",
+                _ => continue,
+            };
+
+            let Some(port) = fields[1]
+                .rsplit(':')
+                .next()
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            else {
+                continue;
+            };
+
+            let pid = fields[9].parse::<u64>().ok().and_then(|inode| owners.get(&inode).copied());
+
+            ports.push(NetworkPort {
+                port,
+                protocol,
+                state: state.to_string(),
+                pid,
+            });
+        }
+    }
+
+    sort_ports(&mut ports);
+    ports
+}
+
+// a socket shared by several processes (or bound on v4 and v6 by one) would be listed once per entry
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos", windows)), allow(dead_code))]
+fn sort_ports(ports: &mut Vec<NetworkPort>) {
+    ports.sort_by_key(|p| (p.port, p.protocol, p.pid));
+    ports.dedup_by(|a, b| a.port == b.port && a.protocol == b.protocol && a.pid == b.pid);
+}
+
+#[cfg(target_os = "linux")]
+fn read_unix_sockets(owners: &HashMap<u64, u32>) -> Vec<UnixSocket> {
+    let Ok(contents) = std::fs::read_to_string("/proc/net/unix") else {
+        return Vec::new();
+    };
+
+    // columns are: Num RefCount Protocol Flags Type St Inode [Path]
+    let mut sockets: Vec<UnixSocket> = contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                return None;
+            }
+
+            let inode = fields[6].parse::<u64>().ok()?;
+            let pid = *owners.get(&inode)?;
+
+            Some(UnixSocket {
+                pid,
+                path: fields.get(7).map(|path| path.to_string()),
+                state: u8::from_str_radix(fields[5], 16).unwrap_or(0),
+            })
+        })
+        .collect();
+
+    sockets.sort_by_key(|s| s.pid);
+    sockets
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_inode_owners() -> HashMap<u64, u32> {
+    HashMap::new()
+}
+
+// there are no socket inodes, libproc lists the sockets of each process along with their state
+#[cfg(target_os = "macos")]
+fn read_listening_ports(_owners: &HashMap<u64, u32>) -> Vec<NetworkPort> {
+    let mut ports = Vec::new();
+    for pid in libproc::all_pids() {
+        for fd in libproc::socket_fds(pid) {
+            ports.extend(libproc::listening_port(pid, fd));
+        }
+    }
+    sort_ports(&mut ports);
+    ports
+}
+
+#[cfg(windows)]
+fn read_listening_ports(_owners: &HashMap<u64, u32>) -> Vec<NetworkPort> {
+    use winapi::shared::iprtrmib::{TCP_TABLE_OWNER_PID_LISTENER, UDP_TABLE_OWNER_PID};
+    use winapi::shared::tcpmib::{MIB_TCP6TABLE_OWNER_PID, MIB_TCPTABLE_OWNER_PID};
+    use winapi::shared::udpmib::{MIB_UDP6TABLE_OWNER_PID, MIB_UDPTABLE_OWNER_PID};
+    use winapi::shared::ws2def::{AF_INET, AF_INET6};
+    use winapi::um::iphlpapi::{GetExtendedTcpTable, GetExtendedUdpTable};
+
+    // ports come in network byte order in the low 16 bits
+    let port = |raw: u32| u16::from_be(raw as u16);
+    let mut ports = Vec::new();
+    let mut push = |port: u16, protocol: Protocol, state: &str, pid: u32| {
+        ports.push(NetworkPort {
+            port,
+            protocol,
+            state: state.to_string(),
+            pid: Some(pid),
+        });
+    };
+
+    // SAFETY: the tables are filled by the calls below and only read within dwNumEntries
+    unsafe {
+        if let Some(table) = extended_table(|buf, size| {
+            GetExtendedTcpTable(buf, size, 0, AF_INET as u32, TCP_TABLE_OWNER_PID_LISTENER, 0)
+        }) {
+            let table = &*(table.as_ptr() as *const MIB_TCPTABLE_OWNER_PID);
+            for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+                push(port(row.dwLocalPort), Protocol::Tcp, "LISTEN", row.dwOwningPid);
+            }
+        }
+        if let Some(table) = extended_table(|buf, size| {
+            GetExtendedTcpTable(buf, size, 0, AF_INET6 as u32, TCP_TABLE_OWNER_PID_LISTENER, 0)
+        }) {
+            let table = &*(table.as_ptr() as *const MIB_TCP6TABLE_OWNER_PID);
+            for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+                push(port(row.dwLocalPort), Protocol::Tcp6, "LISTEN", row.dwOwningPid);
+            }
+        }
+        if let Some(table) = extended_table(|buf, size| {
+            GetExtendedUdpTable(buf, size, 0, AF_INET as u32, UDP_TABLE_OWNER_PID, 0)
+        }) {
+            let table = &*(table.as_ptr() as *const MIB_UDPTABLE_OWNER_PID);
+            for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+                push(port(row.dwLocalPort), Protocol::Udp, "UNCONN", row.dwOwningPid);
+            }
+        }
+        if let Some(table) = extended_table(|buf, size| {
+            GetExtendedUdpTable(buf, size, 0, AF_INET6 as u32, UDP_TABLE_OWNER_PID, 0)
+        }) {
+            let table = &*(table.as_ptr() as *const MIB_UDP6TABLE_OWNER_PID);
+            for row in std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) {
+                push(port(row.dwLocalPort), Protocol::Udp6, "UNCONN", row.dwOwningPid);
+            }
+        }
+    }
+
+    sort_ports(&mut ports);
+    ports
+}
+
+// calls one of the GetExtended*Table functions until the buffer is big enough, the table can grow
+// between the call that sizes it and the one that fills it. u64s keep the buffer aligned for the rows.
+#[cfg(windows)]
+fn extended_table(get: impl Fn(*mut winapi::ctypes::c_void, &mut u32) -> u32) -> Option<Vec<u64>> {
+    use winapi::shared::winerror::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+
+    let mut buf: Vec<u64> = vec![0; 2048];
+    let mut size = (buf.len() * 8) as u32;
+    for _ in 0..4 {
+        match get(buf.as_mut_ptr().cast(), &mut size) {
+            NO_ERROR => return Some(buf),
+            ERROR_INSUFFICIENT_BUFFER => buf = vec![0; (size as usize).div_ceil(8)],
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn read_listening_ports(_owners: &HashMap<u64, u32>) -> Vec<NetworkPort> {
+    Vec::new()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_unix_sockets(_owners: &HashMap<u64, u32>) -> Vec<UnixSocket> {
+    Vec::new()
+}
+
+// the socket part of <sys/proc_info.h>, libc only has the calls and the fd listing
+#[cfg(target_os = "macos")]
+#[allow(dead_code)] // most fields are only there to put the ones we read at their C offsets
+mod libproc {
+    use super::{NetworkPort, Protocol};
+    use std::mem::size_of;
+
+    const PROC_PIDFDSOCKETINFO: libc::c_int = 3;
+    const SOCKINFO_IN: libc::c_int = 1;
+    const SOCKINFO_TCP: libc::c_int = 2;
+    const TSI_S_LISTEN: libc::c_int = 1;
+
+    #[repr(C)]
+    struct SocketFdInfo {
+        pfi: [u64; 3], // proc_fileinfo
+        psi: SocketInfo,
+    }
+
+    #[repr(C)]
+    struct SocketInfo {
+        soi_stat: [u64; 17], // vinfo_stat
+        soi_so: u64,
+        soi_pcb: u64,
+        soi_type: libc::c_int,
+        soi_protocol: libc::c_int,
+        soi_family: libc::c_int,
+        soi_shorts: [i16; 8], // options, linger, state, qlen, incqlen, qlimit, timeo, error
+        soi_oobmark: u32,
+        soi_rcv: [u32; 6], // sockbuf_info
+        soi_snd: [u32; 6],
+        soi_kind: libc::c_int,
+        rfu_1: u32,
+        soi_proto: SocketProto,
+    }
+
+    #[repr(C)]
+    union SocketProto {
+        pri_in: InSockInfo,
+        pri_tcp: TcpSockInfo,
+        pri_un: [u64; 66], // un_sockinfo, the largest member
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct InSockInfo {
+        insi_fport: libc::c_int, // network byte order
+        insi_lport: libc::c_int,
+        insi_gencnt: u64,
+        insi_flags: u32,
+        insi_flow: u32,
+        insi_vflag: u8,
+        insi_ip_ttl: u8,
+        rfu_1: u32,
+        insi_faddr: [u32; 4],
+        insi_laddr: [u32; 4],
+        insi_v4: u8,
+        insi_v6: [u32; 3],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TcpSockInfo {
+        tcpsi_ini: InSockInfo,
+        tcpsi_state: libc::c_int,
+        tcpsi_timer: [libc::c_int; 4],
+        tcpsi_mss: libc::c_int,
+        tcpsi_flags: u32,
+        rfu_1: u32,
+        tcpsi_tp: u64,
+    }
+
+    pub(super) fn all_pids() -> Vec<u32> {
+        // SAFETY: a null buffer only asks for the count, the second call writes at most `pids.len()` entries
+        unsafe {
+            let count = libc::proc_listallpids(std::ptr::null_mut(), 0);
+            if count <= 0 {
+                return Vec::new();
+            }
+            // room for processes started in between
+            let mut pids: Vec<libc::pid_t> = vec![0; count as usize + 64];
+            let size = (pids.len() * size_of::<libc::pid_t>()) as libc::c_int;
+            let count = libc::proc_listallpids(pids.as_mut_ptr().cast(), size);
+            pids.truncate(count.max(0) as usize);
+            pids.into_iter().filter(|&pid| pid > 0).map(|pid| pid as u32).collect()
+        }
+    }
+
+    pub(super) fn socket_fds(pid: u32) -> Vec<i32> {
+        let pid = pid as libc::c_int;
+        // SAFETY: as above, the second call writes at most `size` bytes into `fds`
+        unsafe {
+            let size = libc::proc_pidinfo(pid, libc::PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0);
+            if size <= 0 {
+                return Vec::new();
+            }
+            let mut fds: Vec<libc::proc_fdinfo> = Vec::with_capacity(size as usize / size_of::<libc::proc_fdinfo>() + 8);
+            let size = (fds.capacity() * size_of::<libc::proc_fdinfo>()) as libc::c_int;
+            let filled = libc::proc_pidinfo(pid, libc::PROC_PIDLISTFDS, 0, fds.as_mut_ptr().cast(), size);
+            fds.set_len(filled.max(0) as usize / size_of::<libc::proc_fdinfo>());
+            fds.into_iter()
+                .filter(|fd| fd.proc_fdtype == libc::PROX_FDTYPE_SOCKET as u32)
+                .map(|fd| fd.proc_fd)
+                .collect()
+        }
+    }
+
+    /// The port `fd` of `pid` listens on, for a listening TCP or an unconnected UDP socket.
+    pub(super) fn listening_port(pid: u32, fd: i32) -> Option<NetworkPort> {
+        // SAFETY: SocketFdInfo is plain data, zeroes are a valid value and the kernel fills it
+        // completely when it returns its size
+        let info = unsafe {
+            let mut info: SocketFdInfo = std::mem::zeroed();
+            let size = size_of::<SocketFdInfo>() as libc::c_int;
+            let filled = libc::proc_pidfdinfo(
+                pid as libc::c_int,
+                fd,
+                PROC_PIDFDSOCKETINFO,
+                (&mut info as *mut SocketFdInfo).cast(),
+                size,
+            );
+            if filled != size {
+                return None;
+            }
+            info
+        };
+        let socket = &info.psi;
+        let v6 = match socket.soi_family {
+            libc::AF_INET => false,
+            libc::AF_INET6 => true,
+            _ => return None,
+        };
+
+        // SAFETY: soi_kind says which member of the union the kernel filled
+        let (protocol, state, inet) = unsafe {
+            match socket.soi_kind {
+                SOCKINFO_TCP if socket.soi_proto.pri_tcp.tcpsi_state == TSI_S_LISTEN => {
+                    let protocol = if v6 { Protocol::Tcp6 } else { Protocol::Tcp };
+                    (protocol, "LISTEN", socket.soi_proto.pri_tcp.tcpsi_ini)
+                }
+                SOCKINFO_IN if socket.soi_protocol == libc::IPPROTO_UDP && socket.soi_proto.pri_in.insi_fport == 0 => {
+                    let protocol = if v6 { Protocol::Udp6 } else { Protocol::Udp };
+                    (protocol, "UNCONN", socket.soi_proto.pri_in)
+                }
+                _ => return None,
+            }
+        };
+
+        Some(NetworkPort {
+            port: u16::from_be(inet.insi_lport as u16),
+            protocol,
+            state: state.to_string(),
+            pid: Some(pid),
+        })
+    }
+}
+
+// per-process utilisation and memory from NVIDIA's management library, the proprietary driver's
+// replacement for DRM fdinfo. libnvidia-ml is loaded at runtime, init fails without it
+#[cfg(feature = "gpu")]
+mod nvml {
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::Nvml;
+    use std::collections::HashMap;
+
+    pub struct NvmlClients {
+        nvml: Nvml,
+        last_sample_us: u64, // newest utilisation sample seen, NVML only returns later ones
+    }
+
+    #[derive(Default)]
+    pub struct GpuClient {
+        pub usage: f32, // busiest of the SM, encoder and decoder, in percent
+        pub memory: u64,
+    }
+
+    impl NvmlClients {
+        pub fn init() -> Option<Self> {
+            Nvml::init().ok().map(|nvml| Self { nvml, last_sample_us: 0 })
+        }
+
+        /// Every process with a context on any device, by pid.
+        pub fn read(&mut self) -> HashMap<u32, GpuClient> {
+            let mut clients: HashMap<u32, GpuClient> = HashMap::new();
+            let mut newest_sample_us = self.last_sample_us;
+            for index in 0..self.nvml.device_count().unwrap_or(0) {
+                let Ok(device) = self.nvml.device_by_index(index) else {
+                    continue;
+                };
+                // a process with both a compute and a graphics context is listed twice
+                let mut memory: HashMap<u32, u64> = HashMap::new();
+                let compute = device.running_compute_processes().unwrap_or_default();
+                let graphics = device.running_graphics_processes().unwrap_or_default();
+                for process in compute.into_iter().chain(graphics) {
+                    let used = if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory { bytes } else { 0 };
+                    let entry = memory.entry(process.pid).or_default();
+                    *entry = (*entry).max(used);
+                }
+                for (pid, used) in memory {
+                    clients.entry(pid).or_default().memory += used;
+                }
+                // errors with NotFound when nothing ran since the last sample
+                for sample in device.process_utilization_stats(self.last_sample_us).unwrap_or_default() {
+                    newest_sample_us = newest_sample_us.max(sample.timestamp);
+                    let busiest = sample.sm_util.max(sample.enc_util).max(sample.dec_util) as f32;
+                    let client = clients.entry(sample.pid).or_default();
+                    client.usage = client.usage.max(busiest.min(100.0));
+                }
+            }
+            self.last_sample_us = newest_sample_us;
+            clients
+        }
+    }
+}
@@ -1,48 +1,696 @@
+use anyhow::Result;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{
-        Block, Borders, Gauge, Paragraph, Row, Sparkline, Table, Wrap,
+        Block, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Sparkline, Table, Tabs, Wrap,
     },
     Frame,
 };
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::system::{SortOrder, SystemMonitor};
+use crate::app::{AffinityEditor, AppMode, AppState, FollowState, IoPriorityPicker, KillDialog};
+use crate::column::ProcessColumn;
+use crate::config::Theme;
+use crate::filter::FilterMode;
+use crate::system::{
+    signal_label, CgroupQuota, MemoryPressure, NetworkPort, ProcessDetails, ProcessInfo, SeccompMode, SortDirection, SortOrder, SystemMonitor, SystemSnapshot,
+    UnixSocket, DIALOG_SIGNALS, SOCKETS_SUPPORTED,
+};
+
+pub const MIN_TERMINAL_WIDTH: u16 = 40;
+pub const MIN_TERMINAL_HEIGHT: u16 = 15;
+
+// below these a bordered widget has no room left for content, so drawing is skipped
+const MIN_WIDTH: u16 = 10;
+const MIN_HEIGHT: u16 = 5;
+const BAR_HEIGHT: u16 = 3; // header, footer and gauges are a single line inside a border
+const PANEL_HEIGHT: u16 = 8; // network/disk panel: five rows, or two sparklines of two lines each
+
+// memory lives on a different node than the cpu the process is running on
+const CROSS_NUMA_COLOR: Color = Color::Rgb(255, 165, 0);
+const MARKED_COLOR: Color = Color::Magenta; // background of processes marked for a batch kill
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortsTab {
+    Inet,
+    Unix,
+}
+
+/// What sits above the process table, cycled with Tab. The network and disk panels go below
+/// the system stats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanelView {
+    Stats,
+    Network,
+    Disk,
+    ProcessOnly,
+}
+
+impl PanelView {
+    pub fn next(self) -> Self {
+        match self {
+            PanelView::Stats => PanelView::Network,
+            PanelView::Network => PanelView::Disk,
+            PanelView::Disk => PanelView::ProcessOnly,
+            PanelView::ProcessOnly => PanelView::Stats,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PanelView::Stats => "stats",
+            PanelView::Network => "network",
+            PanelView::Disk => "disks",
+            PanelView::ProcessOnly => "processes only",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutPreset {
+    Standard,
+    Wide,
+    Compact,
+    CpuFocus,
+}
+
+impl LayoutPreset {
+    pub fn next(self) -> Self {
+        match self {
+            LayoutPreset::Standard => LayoutPreset::Wide,
+            LayoutPreset::Wide => LayoutPreset::Compact,
+            LayoutPreset::Compact => LayoutPreset::CpuFocus,
+            LayoutPreset::CpuFocus => LayoutPreset::Standard,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LayoutPreset::Standard => "standard",
+            LayoutPreset::Wide => "wide",
+            LayoutPreset::Compact => "compact",
+            LayoutPreset::CpuFocus => "cpu focus",
+        }
+    }
+
+    pub fn config(self) -> LayoutConfig {
+        match self {
+            LayoutPreset::Standard => LayoutConfig {
+                margin: 1,
+                header: Constraint::Length(3),
+                stats: Constraint::Length(8),
+                table: Constraint::Min(10),
+                footer: Constraint::Length(3),
+                cpu_share: 50,
+                max_cpu_gauges: 4,
+            },
+            LayoutPreset::Wide => LayoutConfig {
+                margin: 0,
+                header: Constraint::Length(3),
+                stats: Constraint::Length(8),
+                table: Constraint::Min(10),
+                footer: Constraint::Length(3),
+                cpu_share: 65,
+                max_cpu_gauges: 4,
+            },
+            LayoutPreset::Compact => LayoutConfig {
+                margin: 0,
+                header: Constraint::Length(0),
+                stats: Constraint::Length(3),
+                table: Constraint::Min(5),
+                footer: Constraint::Length(3),
+                cpu_share: 50,
+                max_cpu_gauges: 1,
+            },
+            LayoutPreset::CpuFocus => LayoutConfig {
+                margin: 1,
+                header: Constraint::Length(3),
+                stats: Constraint::Percentage(60),
+                table: Constraint::Percentage(40),
+                footer: Constraint::Length(3),
+                cpu_share: 70,
+                max_cpu_gauges: usize::MAX,
+            },
+        }
+    }
+}
+
+/// Vertical split of the screen plus how the stats row is divided.
+#[derive(Debug, Clone)]
+pub struct LayoutConfig {
+    pub margin: u16,
+    pub header: Constraint,
+    pub stats: Constraint,
+    pub table: Constraint,
+    pub footer: Constraint,
+    pub cpu_share: u16, // percentage of the stats row used by cpu gauges, memory gets the rest
+    pub max_cpu_gauges: usize,
+}
+
+impl ProcessColumn {
+    fn title(self) -> &'static str {
+        match self {
+            ProcessColumn::User => "User",
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Name => "Name",
+            ProcessColumn::Cpu => "CPU%",
+            ProcessColumn::Memory => "Memory",
+            ProcessColumn::MemoryPercent => "Mem%",
+            ProcessColumn::Threads => "Threads",
+            ProcessColumn::Nice => "Nice",
+            ProcessColumn::DiskRead => "Read",
+            ProcessColumn::DiskWrite => "Write",
+            ProcessColumn::Started => "Started",
+            ProcessColumn::Gpu => "GPU%",
+        }
+    }
 
+    fn sort(self) -> Option<SortOrder> {
+        match self {
+            ProcessColumn::User => Some(SortOrder::User),
+            ProcessColumn::Pid => Some(SortOrder::Pid),
+            ProcessColumn::Name => Some(SortOrder::Name),
+            ProcessColumn::Cpu => Some(SortOrder::Cpu),
+            ProcessColumn::Memory => Some(SortOrder::Memory),
+            ProcessColumn::MemoryPercent => None,
+            ProcessColumn::Threads => Some(SortOrder::Threads),
+            ProcessColumn::Nice => Some(SortOrder::Nice),
+            ProcessColumn::DiskRead => Some(SortOrder::DiskRead),
+            ProcessColumn::DiskWrite => Some(SortOrder::DiskWrite),
+            ProcessColumn::Started => Some(SortOrder::StartTime),
+            ProcessColumn::Gpu => Some(SortOrder::GpuUsage),
+        }
+    }
+
+    // the disk I/O columns only show up while sorting by them, likewise the start time and GPU
+    fn visible(self, sort_order: &SortOrder) -> bool {
+        match self {
+            ProcessColumn::DiskRead | ProcessColumn::DiskWrite => {
+                matches!(sort_order, SortOrder::DiskRead | SortOrder::DiskWrite)
+            }
+            ProcessColumn::Started => *sort_order == SortOrder::StartTime,
+            ProcessColumn::Gpu => *sort_order == SortOrder::GpuUsage,
+            _ => true,
+        }
+    }
+}
+
+/// Column widths a drag on the header can set.
+pub const MIN_COLUMN_WIDTH: u16 = 4;
+pub const MAX_COLUMN_WIDTH: u16 = 40;
+
+/// Where the last drawn process table put its header cells, for mouse hits.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderLayout {
+    pub y: u16,
+    pub cells: Vec<(u16, u16, ProcessColumn)>, // x and width of each column's cell
+}
+
+impl HeaderLayout {
+    /// The columns left and right of the separator within a cell of (x, y).
+    pub fn separator_at(&self, x: u16, y: u16) -> Option<(ProcessColumn, ProcessColumn)> {
+        if y != self.y {
+            return None;
+        }
+        self.cells.windows(2).find_map(|pair| {
+            let (start, width, left) = pair[0];
+            let separator = start + width;
+            (x + 1 >= separator && x <= separator + 1).then_some((left, pair[1].2))
+        })
+    }
+
+    /// The column whose header cell contains (x, y).
+    pub fn column_at(&self, x: u16, y: u16) -> Option<ProcessColumn> {
+        if y != self.y {
+            return None;
+        }
+        self.cells
+            .iter()
+            .find(|(start, width, _)| (*start..start + width).contains(&x))
+            .map(|(_, _, column)| *column)
+    }
+}
+
+// the table's columns are laid out here rather than by ratatui so mouse hits can be mapped
+// back onto them; the name column takes the width the fixed ones leave
+fn header_layout(area: Rect, state: &AppState) -> HeaderLayout {
+    let columns: Vec<ProcessColumn> = state
+        .column_order
+        .iter()
+        .copied()
+        .filter(|column| column.visible(&state.sort_state.order))
+        .collect();
+    let width = |column: ProcessColumn| state.column_widths[column as usize];
+    let inner_width = area.width.saturating_sub(2);
+    let fixed: u16 = columns
+        .iter()
+        .filter(|column| **column != ProcessColumn::Name)
+        .map(|column| width(*column) + 1)
+        .sum();
+    let name_width = inner_width.saturating_sub(fixed).max(width(ProcessColumn::Name));
+
+    let mut x = area.x + 1;
+    let cells = columns
+        .into_iter()
+        .map(|column| {
+            let width = if column == ProcessColumn::Name { name_width } else { width(column) };
+            let cell = (x, width, column);
+            x += width + 1;
+            cell
+        })
+        .collect();
+    HeaderLayout { y: area.y + 1, cells }
+}
+
+/// `processes` are the table rows in display order, `depths` their depth in the tree views.
 pub fn draw_ui(
     f: &mut Frame,
     monitor: &SystemMonitor,
-    selected_process: usize,
-    sort_order: &SortOrder,
-    debug_mode: bool,
-) {
+    processes: &[ProcessInfo],
+    depths: &[usize],
+    state: &mut AppState,
+) -> Result<()> {
+    let layout = &state.layout.config();
+    let sort = &state.sort_state;
+    let selected_process = state.selected;
+    let theme = &state.theme;
+
+    let size = f.size();
+    if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+        draw_too_small(f, size);
+        return Ok(());
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(1)
+        .margin(layout.margin)
         .constraints([
-            Constraint::Length(3),  // header
-            Constraint::Length(8),  // cpu/memory info
-            Constraint::Min(10),    // process table
-            Constraint::Length(2),  // footer
+            layout.header, // header
+            // cpu/memory info
+            if state.panel_view == PanelView::ProcessOnly { Constraint::Length(0) } else { layout.stats },
+            // network or disk panel
+            Constraint::Length(match state.panel_view {
+                PanelView::Network | PanelView::Disk => PANEL_HEIGHT,
+                PanelView::Stats | PanelView::ProcessOnly => 0,
+            }),
+            layout.table,  // process table
+            layout.footer, // footer
         ])
         .split(f.size());
 
-    draw_header(f, chunks[0], monitor);
-    draw_system_stats(f, chunks[1], monitor);
-    draw_process_table(f, chunks[2], monitor, selected_process, sort_order);
-    draw_footer(f, chunks[3], debug_mode);
+    if chunks[0].height > 0 {
+        draw_header(f, chunks[0], monitor, theme)?;
+    }
+    draw_system_stats(f, chunks[1], monitor, layout)?;
+    match state.panel_view {
+        PanelView::Network => draw_network_stats(f, chunks[2], monitor, state.selected_interface, theme)?,
+        PanelView::Disk => draw_disk_stats(f, chunks[2], monitor, theme)?,
+        PanelView::Stats | PanelView::ProcessOnly => {}
+    }
+    if let Some(pid) = state.detail_pid() {
+        draw_process_detail(
+            f,
+            chunks[3],
+            pid,
+            monitor.get_process_by_pid(pid).as_ref(),
+            &monitor.get_process_details(pid),
+            monitor.get_cgroup_quota(pid).as_ref(),
+            theme,
+        )?;
+    } else {
+        state.page_size = table_rows(chunks[3]);
+        state.scroll_offset = scroll_offset(state.scroll_offset, selected_process, processes.len(), state.page_size);
+        let scroll = state.scroll_offset;
+        let plain_table = state.ports_tab.is_none()
+            && state.follow.is_none()
+            && !state.container_view
+            && !state.diff_mode
+            && !state.swap_view
+            && !state.priority_view;
+        state.header_layout = plain_table.then(|| header_layout(chunks[3], state));
+        match (state.ports_tab, state.follow.as_ref()) {
+            (Some(tab), _) => draw_network_view(f, chunks[3], monitor, &sort.order, tab, theme),
+            (None, Some(follow)) if follow.root_exited => draw_follow_summary(f, chunks[3], follow),
+            (None, Some(follow)) => {
+                draw_follow_tree(f, chunks[3], processes, depths, scroll, follow, state)
+            }
+            (None, None) if state.diff_mode => match &state.baseline_snapshot {
+                Some(baseline) => draw_diff_table(f, chunks[3], processes, baseline, selected_process, scroll, theme),
+                None => Ok(()),
+            },
+            (None, None) if state.container_view => draw_container_table(f, chunks[3], monitor, theme),
+            (None, None) if state.swap_view => draw_swap_table(f, chunks[3], processes, selected_process, scroll, theme),
+            (None, None) if state.priority_view => draw_priority_table(f, chunks[3], processes, selected_process, scroll, theme),
+            (None, None) => draw_process_table(f, chunks[3], processes, depths, scroll, state, theme),
+        }?;
+    }
+    draw_footer(f, chunks[4], state, theme)?;
+
+    if let Some(editor) = &state.affinity_editor {
+        draw_affinity_editor(f, centered_rect(40, 60, f.size()), editor)?;
+    }
+    if let Some(picker) = &state.io_priority_picker {
+        draw_io_priority_picker(f, centered_rect(40, 60, f.size()), picker)?;
+    }
+    if state.show_legend {
+        draw_legend_popup(f, centered_rect(60, 70, f.size()), theme)?;
+    }
+    if let Some(targets) = &state.kill_confirm {
+        draw_kill_confirm(f, centered_rect(50, 60, f.size()), targets)?;
+    }
+    match &state.mode {
+        AppMode::KillDialog(dialog) => draw_kill_dialog(f, centered_rect(40, 40, f.size()), dialog)?,
+        AppMode::KillConfirm(dialog) => draw_kill_prompt(f, centered_rect(50, 20, f.size()), dialog)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn draw_kill_prompt(f: &mut Frame, area: Rect, dialog: &KillDialog) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
+    let popup = Paragraph::new(Line::from(Span::styled(
+        dialog.prompt(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title("Confirm (y to go ahead, any other key to cancel)"),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+    Ok(())
+}
+
+fn draw_kill_dialog(f: &mut Frame, area: Rect, dialog: &KillDialog) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let items: Vec<ListItem> = DIALOG_SIGNALS.iter().map(|&signal| ListItem::new(signal_label(signal))).collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(format!("Signal for {} (PID {}) (Enter send, Esc cancel)", dialog.name, dialog.pid)),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+    let mut state = ListState::default();
+    state.select(Some(dialog.cursor));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+    Ok(())
+}
+
+fn draw_kill_confirm(f: &mut Frame, area: Rect, targets: &[(u32, String)]) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let mut text = vec![Line::from(format!("Kill these {} processes with SIGKILL?", targets.len())), Line::from("")];
+    text.extend(
+        targets
+            .iter()
+            .map(|(pid, name)| Line::from(vec![Span::raw(format!("{:<8} ", pid)), Span::raw(name.as_str())])),
+    );
+    let popup = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title("Confirm kill (y to kill, any other key to cancel)"),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+    Ok(())
+}
+
+fn draw_too_small(f: &mut Frame, size: Rect) {
+    let message = format!(
+        "Terminal too small — please resize (min {}×{})",
+        MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT
+    );
+    // vertically center by hand, Paragraph only aligns horizontally; two rows leave room to wrap
+    let height = size.height.min(2);
+    let area = Rect {
+        y: size.y + (size.height - height) / 2,
+        height,
+        ..size
+    };
+    let paragraph = Paragraph::new(message)
+        .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
+
+// compact counter formatting: 950, 1.2K, 3.4M
+pub fn format_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}K", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// Sizes in the largest unit that keeps the value at or above 1, e.g. "512B", "1.5GB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["TB", "GB", "MB", "KB"];
+    for (i, unit) in UNITS.iter().enumerate() {
+        let scale = 1u64 << (10 * (UNITS.len() - i));
+        if bytes >= scale {
+            return format!("{:.1}{}", bytes as f64 / scale as f64, unit);
+        }
+    }
+    format!("{}B", bytes)
+}
+
+/// Keeps the end of `text`, which is the informative part of a path, marking the cut with `…`.
+fn truncate_left(text: &str, max_chars: usize) -> String {
+    let len = text.chars().count();
+    if len <= max_chars {
+        return text.to_string();
+    }
+    let keep = max_chars.saturating_sub(1);
+    format!("…{}", text.chars().skip(len - keep).collect::<String>())
+}
+
+/// How long ago something happened, in its two largest units, e.g. "2h 34m ago".
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h {}m ago", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h ago", seconds / 86400, seconds % 86400 / 3600),
+    }
+}
+
+pub fn format_rate(bytes_per_sec: u64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec))
+}
+
+/// Network throughput in bits per second, e.g. "450 Mbps" or "1.2 Gbps".
+pub fn format_bit_rate(bytes_per_sec: u64) -> String {
+    let bits = bytes_per_sec as f64 * 8.0;
+    match bits {
+        b if b >= 1e9 => format!("{:.1} Gbps", b / 1e9),
+        b if b >= 1e6 => format!("{:.0} Mbps", b / 1e6),
+        b if b >= 1e3 => format!("{:.0} Kbps", b / 1e3),
+        b => format!("{:.0} bps", b),
+    }
+}
+
+pub fn format_cpu_list(cpus: &[u32]) -> String {
+    cpus.iter().map(|cpu| cpu.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// rows left for data in a bordered table with a header and its bottom margin
+fn table_rows(area: Rect) -> usize {
+    area.height.saturating_sub(4) as usize
+}
+
+/// Keeps `selected` on screen while moving the offset as little as possible.
+fn scroll_offset(offset: usize, selected: usize, row_count: usize, visible: usize) -> usize {
+    if visible == 0 {
+        return 0;
+    }
+    let offset = if selected < offset {
+        selected
+    } else if selected >= offset + visible {
+        selected + 1 - visible
+    } else {
+        offset
+    };
+    offset.min(row_count.saturating_sub(visible))
+}
+
+fn too_small(area: Rect, min_height: u16) -> bool {
+    area.width < MIN_WIDTH || area.height < min_height
+}
+
+/// A rectangle of `percent_x` by `percent_y` centered in `r`, for popups.
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+fn draw_affinity_editor(f: &mut Frame, area: Rect, editor: &AffinityEditor) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let items: Vec<ListItem> = editor
+        .cpus
+        .iter()
+        .enumerate()
+        .map(|(cpu, &checked)| ListItem::new(format!("[{}] CPU {}", if checked { "x" } else { " " }, cpu)))
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(format!("Affinity for PID {} (Space toggle, Enter apply, Esc cancel)", editor.pid))
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+    let mut state = ListState::default();
+    state.select(Some(editor.cursor));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+    Ok(())
+}
+
+fn draw_io_priority_picker(f: &mut Frame, area: Rect, picker: &IoPriorityPicker) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let items: Vec<ListItem> = picker
+        .choices
+        .iter()
+        .map(|choice| ListItem::new(choice.to_string()))
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(format!("I/O priority for PID {} (Enter apply, Esc cancel)", picker.pid))
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+    let mut state = ListState::default();
+    state.select(Some(picker.cursor));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+    Ok(())
+}
+
+/// What the colors in the rest of the UI mean, closed by any key.
+fn draw_legend_popup(f: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let swatch = |text: &'static str, color: Color| Cell::from(Span::styled(text, Style::default().fg(color)));
+    let entries = [
+        (swatch("██", Color::Green), "CPU / memory gauge: healthy"),
+        (swatch("██", Color::Yellow), "Gauge: busy, over 50% CPU or 60% memory"),
+        (swatch("██", Color::Red), "Gauge: overloaded, over 80% CPU or 85% memory"),
+        (swatch("+", Color::Green), "Process started since the last refresh"),
+        (swatch("- name", Color::DarkGray), "Process exited, kept for a few seconds"),
+        (Cell::from(Span::styled("name", Style::default().add_modifier(Modifier::DIM))), "Kernel thread"),
+        (swatch("name", CROSS_NUMA_COLOR), "Running on a CPU outside its memory's NUMA node"),
+        (swatch("●", health_color(100)), "Health score 80 or better"),
+        (swatch("●", health_color(50)), "Health score 50 to 79"),
+        (swatch("●", health_color(0)), "Health score below 50"),
+        (swatch("⚡", Color::Yellow), "Holds CAP_SYS_ADMIN or CAP_NET_ADMIN"),
+        (swatch("⚠", Color::Red), "Stuck in uninterruptible sleep (hung)"),
+        (swatch("[D]", Color::Yellow), "Executable deleted, restart to run the new one (--show-deleted)"),
+        (swatch("0", nice_level_color(0)), "Nice 0 or below, normal or raised priority"),
+        (swatch("5", nice_level_color(5)), "Nice 1 to 10"),
+        (swatch("19", nice_level_color(19)), "Nice above 10"),
+        (swatch("Header", theme.sort_column), "Column the table is sorted by"),
+        (Cell::from(Span::styled("    ", Style::default().bg(theme.selected))), "Selected row"),
+        (Cell::from(Span::styled("name", search_match_style())), "Name matches the search"),
+        (Cell::from(Span::styled("    ", Style::default().bg(MARKED_COLOR))), "Marked for a batch kill (v)"),
+    ];
+    let rows = entries
+        .into_iter()
+        .map(|(swatch, description)| Row::new(vec![swatch, Cell::from(description)]));
+
+    let legend = Table::new(rows)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent))
+                .title("Color legend (any key to close)"),
+        )
+        .widths(&[Constraint::Length(8), Constraint::Percentage(100)])
+        .column_spacing(2);
+
+    f.render_widget(Clear, area);
+    f.render_widget(legend, area);
+    Ok(())
 }
 
-fn draw_header(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
+fn draw_header(f: &mut Frame, area: Rect, monitor: &SystemMonitor, theme: &Theme) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
     let system_info = monitor.get_system_info();
+    let inodes = monitor.get_inode_usage();
+    let pressure = monitor.estimate_memory_pressure();
     let uptime_hours = system_info.uptime / 3600;
     let uptime_mins = (system_info.uptime % 3600) / 60;
 
-    let header_text = vec![
+    let mut header_text = vec![
         Line::from(vec![
-            Span::styled("SysTop", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("SysTop", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)),
             Span::raw(" - System Monitor"),
+            Span::raw(format!(
+                " | Inodes: {}/{} | Files: {}/{}",
+                format_count(inodes.used),
+                format_count(inodes.total),
+                format_count(inodes.open_files),
+                // systemd raises file-max to LONG_MAX
+                if inodes.max_files >= i64::MAX as u64 { "unlimited".to_string() } else { format_count(inodes.max_files) }
+            )),
+            Span::raw(" | Memory pressure: "),
+            Span::styled(pressure.name(), Style::default().fg(memory_pressure_color(pressure))),
         ]),
         Line::from(vec![
             Span::raw("Host: "),
@@ -54,61 +702,236 @@ fn draw_header(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
             ),
         ]),
     ];
+    if let Some(entropy) = monitor.get_entropy_pool_size() {
+        header_text[1].spans.push(Span::raw(" | Entropy: "));
+        header_text[1].spans.push(Span::styled(
+            format!("{} bits", entropy),
+            Style::default().fg(match entropy {
+                0..=255 => Color::Red,
+                256..=1000 => Color::Yellow,
+                _ => Color::Green,
+            }),
+        ));
+    }
 
     let header = Paragraph::new(header_text)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Blue)))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border)))
         .alignment(Alignment::Left);
 
     f.render_widget(header, area);
+    Ok(())
+}
+
+fn memory_pressure_color(pressure: MemoryPressure) -> Color {
+    match pressure {
+        MemoryPressure::None => Color::Green,
+        MemoryPressure::Low => Color::Reset,
+        MemoryPressure::Medium => Color::Yellow,
+        MemoryPressure::High => Color::LightRed,
+        MemoryPressure::Critical => Color::Red,
+    }
 }
 
-fn draw_system_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
+fn draw_system_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor, layout: &LayoutConfig) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints([
+            Constraint::Percentage(layout.cpu_share),
+            Constraint::Percentage(100 - layout.cpu_share),
+        ])
         .split(area);
 
-    draw_cpu_stats(f, chunks[0], monitor);
-    draw_memory_stats(f, chunks[1], monitor);
+    draw_cpu_stats(f, chunks[0], monitor, layout.max_cpu_gauges)?;
+    draw_memory_stats(f, chunks[1], monitor)?;
+    Ok(())
 }
 
-fn draw_cpu_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
+fn draw_cpu_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor, max_gauges: usize) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
     let cpu_info = monitor.get_cpu_info();
-    
+    let efficiency_scores = monitor.get_cpu_efficiency_scores();
+    let numa = monitor.get_numa_topology();
+    let total_usage = monitor.get_total_cpu_usage();
+    let breakdown = monitor.get_cpu_time_breakdown();
+    let iowait = monitor.get_io_wait_percent();
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+
+    let total_gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("CPU Total"))
+        .gauge_style(Style::default().fg(match total_usage as u16 {
+            0..=50 => Color::Green,
+            51..=80 => Color::Yellow,
+            _ => Color::Red,
+        }))
+        .percent((total_usage as u16).min(100))
+        .label(format!("{:.1}%", total_usage));
+    f.render_widget(total_gauge, sections[0]);
+
+    let breakdown_line = Line::from(format!(
+        "usr: {:.0}% | sys: {:.0}% | irq: {:.0}% | sirq: {:.0}% | steal: {:.0}% | guest: {:.0}% | idle: {:.0}%",
+        breakdown.user + breakdown.nice,
+        breakdown.system,
+        breakdown.irq,
+        breakdown.softirq,
+        breakdown.steal,
+        // already part of usr, shown on its own since it's what the VMs on this host use
+        breakdown.guest,
+        breakdown.idle
+    ));
+    f.render_widget(Paragraph::new(breakdown_line).style(Style::default().fg(Color::Gray)), sections[1]);
+
+    let iowait_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(18), Constraint::Min(0)])
+        .split(sections[2]);
+    let iowait_color = match iowait {
+        w if w > 50.0 => Color::Red,
+        w if w > 20.0 => Color::Yellow,
+        _ => Color::Gray,
+    };
+    let iowait_line = Line::from(Span::styled(format!("I/O wait: {:.1}%", iowait), Style::default().fg(iowait_color)));
+    f.render_widget(Paragraph::new(iowait_line), iowait_chunks[0]);
+    let iowait_data: Vec<u64> = monitor.get_io_wait_history().iter().map(|&w| w.round() as u64).collect();
+    // on a fixed scale, a flat line near zero is the normal case
+    let iowait_sparkline = Sparkline::default()
+        .data(&iowait_data)
+        .max(100)
+        .style(Style::default().fg(iowait_color));
+    f.render_widget(iowait_sparkline, iowait_chunks[1]);
+
+    // each gauge needs 3 rows, never ask for more than the area can hold
+    let area = sections[3];
+    let gauge_count = cpu_info.len().min(max_gauges).min((area.height / 3) as usize);
+
     let cpu_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(3); cpu_info.len().min(4)]) // show max 4 cores
+        .constraints(vec![Constraint::Length(3); gauge_count])
         .split(area);
 
-    for (i, cpu) in cpu_info.iter().enumerate().take(4) {
+    let usage_color = |usage: f32| match usage as u16 {
+        0..=50 => Color::Green,
+        51..=80 => Color::Yellow,
+        _ => Color::Red,
+    };
+    for (i, cpu) in cpu_info.iter().enumerate().take(gauge_count) {
         if i < cpu_chunks.len() {
+            // usage normalized by clock speed next to the gauge, P- and E-cores differ a lot there
+            let row = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(9)])
+                .split(cpu_chunks[i]);
+            let efficiency = Paragraph::new(format!("{:.1}", efficiency_scores[i]))
+                .alignment(Alignment::Right)
+                .style(Style::default().fg(usage_color(efficiency_scores[i])))
+                .block(Block::default().borders(Borders::ALL).title("Eff"));
+
+            let mut title = match numa.node_of_cpu(i as u32).filter(|_| numa.is_numa()) {
+                Some(node) => format!("CPU {} (N{})", i + 1, node),
+                None => format!("CPU {}", i + 1),
+            };
+            if let Some(governor) = &cpu.governor {
+                let (min, max) = monitor.get_cpu_frequency_range(i);
+                title.push_str(&format!(" {} {}-{} MHz", governor, min, max));
+            }
             let gauge = Gauge::default()
-                .block(Block::default()
-                    .borders(Borders::ALL)
-                    .title(format!("CPU {}", i + 1)))
-                .gauge_style(Style::default().fg(match cpu.usage as u16 {
-                    0..=50 => Color::Green,
-                    51..=80 => Color::Yellow,
-                    _ => Color::Red,
-                }))
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .gauge_style(Style::default().fg(usage_color(cpu.usage)))
                 .percent(cpu.usage as u16)
                 .label(format!("{:.1}%", cpu.usage));
 
-            f.render_widget(gauge, cpu_chunks[i]);
+            f.render_widget(gauge, row[0]);
+            f.render_widget(efficiency, row[1]);
         }
     }
+    Ok(())
 }
 
-fn draw_memory_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
+fn draw_memory_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
     let memory_percent = monitor.get_memory_percent();
     let used_memory = monitor.get_used_memory();
     let total_memory = monitor.get_total_memory();
     let memory_history = monitor.get_memory_history();
+    let swap_percent = monitor.get_swap_percent();
+    let swap_history = monitor.get_swap_history();
+    let numa = monitor.get_numa_topology();
+    let vm_stats = monitor.get_vm_stats();
+
+    // extra one-line readouts between the gauge and the sparkline
+    let mut info_lines = vec![Line::from(vec![
+        Span::raw(format!("PgFault: {}/s | ", format_count(vm_stats.minor_faults_per_sec))),
+        Span::styled(
+            format!("MajFault: {}/s", format_count(vm_stats.major_faults_per_sec)),
+            // major faults hit the disk, a burst usually means swapping
+            Style::default().fg(if vm_stats.major_faults_per_sec > 100 { Color::Red } else { Color::Reset }),
+        ),
+        Span::raw(format!(" | Dirty: {}", format_bytes(vm_stats.dirty_bytes()))),
+        Span::raw(format!(" | Locked: {}", format_bytes(monitor.get_locked_memory()))),
+    ])];
+    if let Some(swappiness) = monitor.get_swappiness() {
+        info_lines[0].spans.push(Span::raw(format!(" | Swappiness: {}", swappiness)));
+    }
+    let (net_rx, net_tx) = monitor.get_total_network_rate();
+    info_lines.push(Line::from(format!(
+        "Disk: ↓ {} ↑ {} | Net: ↓ {} ↑ {}",
+        format_rate(monitor.get_total_disk_read_rate()),
+        format_rate(monitor.get_total_disk_write_rate()),
+        format_bit_rate(net_rx),
+        format_bit_rate(net_tx)
+    )));
+    if numa.is_numa() {
+        info_lines.push(Line::from(
+            numa.nodes
+                .iter()
+                .map(|node| {
+                    format!(
+                        "N{}: {}/{}",
+                        node.id,
+                        format_bytes(node.memory_used),
+                        format_bytes(node.memory_total)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ));
+    }
 
     let memory_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(info_lines.len() as u16),
+            Constraint::Min(3),
+        ])
         .split(area);
+    // the swap gauge goes below the memory gauge when there's room, next to it otherwise
+    let (memory_area, swap_area, history_area) = if memory_chunks[2].height >= BAR_HEIGHT + 3 {
+        let history = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(BAR_HEIGHT), Constraint::Min(3)])
+            .split(memory_chunks[2]);
+        (memory_chunks[0], history[0], history[1])
+    } else {
+        let gauges = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(memory_chunks[0]);
+        (gauges[0], gauges[1], memory_chunks[2])
+    };
 
     // memory gauge
     let memory_gauge = Gauge::default()
@@ -120,76 +943,170 @@ fn draw_memory_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor) {
         }))
         .percent(memory_percent as u16)
         .label(format!(
-            "{:.1}% ({:.1}GB / {:.1}GB)",
+            "{:.1}% ({} / {})",
             memory_percent,
-            used_memory as f64 / 1024.0 / 1024.0 / 1024.0,
-            total_memory as f64 / 1024.0 / 1024.0 / 1024.0
+            format_bytes(used_memory),
+            format_bytes(total_memory)
         ));
 
-    f.render_widget(memory_gauge, memory_chunks[0]);
+    // swap gauge, machines without swap just get an empty bar
+    let swap_total = monitor.get_swap_total();
+    let swap_gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("Swap"))
+        .gauge_style(Style::default().fg(match swap_percent as u16 {
+            0..=25 => Color::Green,
+            26..=60 => Color::Yellow,
+            _ => Color::Red,
+        }))
+        .percent(swap_percent.min(100.0) as u16)
+        .label(if swap_total == 0 {
+            "no swap".to_string()
+        } else {
+            format!(
+                "{:.1}% ({} / {})",
+                swap_percent,
+                format_bytes(monitor.get_swap_used()),
+                format_bytes(swap_total)
+            )
+        });
+
+    f.render_widget(memory_gauge, memory_area);
+    f.render_widget(swap_gauge, swap_area);
+    f.render_widget(Paragraph::new(info_lines), memory_chunks[1]);
 
-    // memory history sparkline
-    if !memory_history.is_empty() {
-        let sparkline_data: Vec<u64> = memory_history.iter().map(|&x| x as u64).collect();
+    // memory and swap history sparklines side by side
+    let history_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(history_area);
+    for (history, title, color, chunk) in [
+        (memory_history, "Memory History", Color::Cyan, history_chunks[0]),
+        (swap_history, "Swap History", Color::Magenta, history_chunks[1]),
+    ] {
+        if history.is_empty() {
+            continue;
+        }
+        let sparkline_data: Vec<u64> = history.iter().map(|&x| x as u64).collect();
         let sparkline = Sparkline::default()
-            .block(Block::default().borders(Borders::ALL).title("Memory History"))
+            .block(Block::default().borders(Borders::ALL).title(title))
             .data(&sparkline_data)
-            .style(Style::default().fg(Color::Cyan));
-        
-        f.render_widget(sparkline, memory_chunks[1]);
+            .style(Style::default().fg(color));
+
+        f.render_widget(sparkline, chunk);
     }
+    Ok(())
 }
 
 fn draw_process_table(
     f: &mut Frame,
     area: Rect,
-    monitor: &SystemMonitor,
-    selected_process: usize,
-    sort_order: &SortOrder,
-) {
-    let processes = monitor.get_processes(sort_order);
-    
-    let header_cells = ["PID", "Name", "CPU%", "Memory", "Mem%"]
-        .iter()
-        .enumerate()
-        .map(|(i, h)| {
-            let style = match (i, sort_order) {
-                (0, SortOrder::Pid) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                (1, SortOrder::Name) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                (2, SortOrder::Cpu) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                (4, SortOrder::Memory) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                _ => Style::default(),
-            };
-            Span::styled(*h, style)
-        });
+    processes: &[ProcessInfo],
+    depths: &[usize],
+    scroll: usize,
+    state: &AppState,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+    let sort = &state.sort_state;
+    let sort_order = &sort.order;
+    let search = state.filter_expr.as_ref().filter(|_| matches!(state.filter_mode, FilterMode::Search(_)));
+
+    let layout = header_layout(area, state);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+    let widths: Vec<Constraint> = layout.cells.iter().map(|(_, width, _)| Constraint::Length(*width)).collect();
+
+    let header_cells = layout.cells.iter().map(|(_, _, column)| {
+        let mut style = if column.sort().as_ref() == Some(sort_order) {
+            Style::default().fg(theme.sort_column).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        // a terminal has no resize cursor, mark the column a drag would resize instead
+        if state.resize_hover == Some(*column) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        Span::styled(column.title(), style)
+    });
 
     let header = Row::new(header_cells)
-        .style(Style::default().bg(Color::Blue))
+        .style(Style::default().bg(theme.table_header))
         .height(1)
         .bottom_margin(1);
 
-    let rows = processes.iter().enumerate().map(|(i, process)| {
-        let style = if i == selected_process {
-            Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+    // depths are all 0 outside tree mode, every process is a root
+    let last_sibling = last_siblings(depths);
+
+    let rows = depths.iter().copied().zip(processes).enumerate().skip(scroll).map(|(i, (depth, process))| {
+        let mut style = if i == state.selected {
+            Style::default().bg(theme.selected).add_modifier(Modifier::BOLD)
         } else {
             Style::default()
         };
+        if process.numa_cross_node {
+            style = style.fg(CROSS_NUMA_COLOR);
+        }
+        if process.kernel_thread {
+            style = style.add_modifier(Modifier::DIM);
+        }
+        if process.exited {
+            style = style.fg(Color::DarkGray);
+        }
+        if state.selected_pids.contains(&process.pid) {
+            style = style.bg(MARKED_COLOR);
+        }
 
-        let memory_mb = process.memory as f64 / 1024.0 / 1024.0;
-        let memory_display = if memory_mb >= 1024.0 {
-            format!("{:.1}GB", memory_mb / 1024.0)
+        let deleted = state.show_deleted && process.executable_deleted;
+        let name_style = if search.is_some_and(|search| search.matches(&process.name)) {
+            search_match_style()
+        } else if deleted {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let mut name = if process.is_new {
+            Line::from(vec![
+                Span::styled("+ ", Style::default().fg(Color::Green)),
+                Span::styled(process.name.as_str(), name_style),
+            ])
+        } else if process.exited {
+            Line::from(format!("- {}", process.name))
         } else {
-            format!("{:.0}MB", memory_mb)
+            Line::from(Span::styled(process.name.as_str(), name_style))
         };
+        if deleted {
+            name.spans.push(Span::styled(" [D]", Style::default().fg(Color::Yellow)));
+        }
+        if process.hung {
+            name.spans.insert(0, Span::styled("⚠ ", Style::default().fg(Color::Red)));
+        }
+        if process.capabilities.is_some_and(|caps| caps.is_privileged()) {
+            name.spans.insert(0, Span::styled("⚡ ", Style::default().fg(Color::Yellow)));
+        }
+        name.spans.insert(0, Span::styled("● ", Style::default().fg(health_color(process.health_score))));
+        if depth > 0 {
+            let connector = if last_sibling[i] { "└─" } else { "├─" };
+            name.spans.insert(0, Span::raw(format!("{}{} ", "  ".repeat(depth - 1), connector)));
+        }
 
-        Row::new(vec![
-            process.pid.to_string(),
-            process.name.clone(),
-            format!("{:.1}", process.cpu_usage),
-            memory_display,
-            format!("{:.2}", process.memory_percent),
-        ])
-        .style(style)
+        let cells = layout.cells.iter().map(|(_, _, column)| match column {
+            ProcessColumn::User => Cell::from(process.user.as_str()),
+            ProcessColumn::Pid => Cell::from(process.pid.to_string()),
+            ProcessColumn::Name => Cell::from(name.clone()),
+            ProcessColumn::Cpu => Cell::from(format!("{:.1}", process.cpu_usage)),
+            ProcessColumn::Memory => Cell::from(format_bytes(process.memory)),
+            ProcessColumn::MemoryPercent => Cell::from(format!("{:.2}", process.memory_percent)),
+            ProcessColumn::Threads => Cell::from(process.threads.to_string()),
+            ProcessColumn::Nice => {
+                Cell::from(process.nice.to_string()).style(Style::default().fg(nice_level_color(process.nice)))
+            }
+            ProcessColumn::DiskRead => Cell::from(format_rate(process.disk_read_rate)),
+            ProcessColumn::DiskWrite => Cell::from(format_rate(process.disk_write_rate)),
+            ProcessColumn::Started => Cell::from(format_age(now.saturating_sub(process.start_time))),
+            ProcessColumn::Gpu => Cell::from(process.gpu_usage.map_or("-".to_string(), |usage| format!("{:.1}", usage))),
+        });
+        Row::new(cells.collect::<Vec<_>>()).style(style)
     });
 
     let process_table = Table::new(rows)
@@ -197,26 +1114,1004 @@ fn draw_process_table(
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Processes (sorted by {:?})", sort_order))
+                .title(format!(
+                    "{}Processes ({}sorted by {}){}",
+                    state.top_n.map(|n| format!("Top {} ", n)).unwrap_or_default(),
+                    if state.tree_mode { "tree, " } else { "" },
+                    match (sort_order, sort.direction) {
+                        (SortOrder::HealthScore, SortDirection::Ascending) => "health ↑ — worst first".to_string(),
+                        (SortOrder::HealthScore, SortDirection::Descending) => "health ↓ — best first".to_string(),
+                        (other, direction) => format!("{} {}", other, direction.arrow()),
+                    },
+                    if state.auto_scroll { " [AUTO]" } else { "" }
+                ))
         )
-        .widths(&[
-            Constraint::Length(8),
-            Constraint::Min(20),
-            Constraint::Length(8),
-            Constraint::Length(10),
-            Constraint::Length(8),
-        ])
+        .widths(&widths)
         .column_spacing(1);
 
     f.render_widget(process_table, area);
+    Ok(())
 }
 
-fn draw_footer(f: &mut Frame, area: Rect, debug_mode: bool) {
-    let mut footer_text = vec![
-        Line::from("Controls: ↑/↓ or j/k (navigate) | K (kill process) | c (sort by CPU) | m (sort by memory) | q (quit)")
-    ];
+/// For rows in depth-first order, whether each is the last child of its parent.
+fn last_siblings(depths: &[usize]) -> Vec<bool> {
+    let mut last = vec![false; depths.len()];
+    // walking backwards, whether a later row at each depth shares the current parent
+    let mut sibling_below: Vec<bool> = Vec::new();
+    for (i, &depth) in depths.iter().enumerate().rev() {
+        sibling_below.resize(depth + 1, false);
+        last[i] = !sibling_below[depth];
+        sibling_below[depth] = true;
+    }
+    last
+}
+
+fn health_color(score: u8) -> Color {
+    match score {
+        0..=49 => Color::Red,
+        50..=79 => Color::Yellow,
+        _ => Color::Green,
+    }
+}
+
+/// "+1.5MB" / "-512B", for the diff view.
+fn format_bytes_delta(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_bytes(delta.unsigned_abs()))
+}
+
+fn draw_diff_table(
+    f: &mut Frame,
+    area: Rect,
+    processes: &[ProcessInfo],
+    baseline: &SystemSnapshot,
+    selected_process: usize,
+    scroll: usize,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let header_cells = ["PID", "Name", "CPU%", "ΔCPU", "Memory", "ΔMemory"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows = processes.iter().enumerate().skip(scroll).map(|(i, process)| {
+        // gone processes count down from their baseline values, new ones up from nothing
+        let (cpu_before, memory_before, color) = match baseline.baseline_of(process) {
+            _ if process.exited => (process.cpu_usage, process.memory, Color::DarkGray),
+            Some(before) => (before.cpu_usage, before.memory, Color::Reset),
+            None => (0.0, 0, Color::Green),
+        };
+        let (cpu_now, memory_now) = if process.exited { (0.0, 0) } else { (process.cpu_usage, process.memory) };
+
+        let mut style = Style::default().fg(color);
+        if i == selected_process {
+            style = style.bg(theme.selected).add_modifier(Modifier::BOLD);
+        }
+
+        Row::new(vec![
+            process.pid.to_string(),
+            process.name.clone(),
+            format!("{:.1}", cpu_now),
+            format!("{:+.1}", cpu_now - cpu_before),
+            format_bytes(memory_now),
+            format_bytes_delta(memory_now as i64 - memory_before as i64),
+        ])
+        .style(style)
+    });
+
+    let diff_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Changes since {} ({} processes, D to go back)",
+                    baseline.timestamp.format("%H:%M:%S"),
+                    processes.len()
+                ))
+        )
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(diff_table, area);
+    Ok(())
+}
+
+fn nice_color(nice: i32) -> Color {
+    match nice {
+        ..=-10 => Color::Red,
+        -9..=-1 => Color::Yellow,
+        _ => Color::Reset,
+    }
+}
+
+// the process table marks anything niced down, unlike nice_color which only cares about boosts
+fn nice_level_color(nice: i32) -> Color {
+    match nice {
+        ..=0 => Color::Green,
+        1..=10 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn draw_priority_table(
+    f: &mut Frame,
+    area: Rect,
+    processes: &[ProcessInfo],
+    selected_process: usize,
+    scroll: usize,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let header_cells = ["PID", "Name", "Nice", "CPU%", "Memory"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows: Vec<Row> = if processes.is_empty() {
+        vec![Row::new(vec![Cell::from(""), Cell::from("No high-priority processes")])]
+    } else {
+        processes
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .map(|(i, process)| {
+                let style = if i == selected_process {
+                    Style::default().bg(theme.selected).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+
+                Row::new(vec![
+                    Cell::from(process.pid.to_string()),
+                    Cell::from(process.name.clone()),
+                    Cell::from(process.nice.to_string()).style(Style::default().fg(nice_color(process.nice))),
+                    Cell::from(format!("{:.1}", process.cpu_usage)),
+                    Cell::from(format_bytes(process.memory)),
+                ])
+                .style(style)
+            })
+            .collect()
+    };
+
+    let priority_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Priority Processes ({} with nice < 0, p to go back)", processes.len()))
+        )
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(10),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(priority_table, area);
+    Ok(())
+}
+
+fn draw_network_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor, selected: usize, theme: &Theme) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+    let networks = monitor.get_network_info();
+    if networks.is_empty() {
+        return Ok(());
+    }
+    // interfaces can go away between the keypress and this refresh
+    let selected = selected.min(networks.len() - 1);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let header = Row::new(["Interface", "RX", "TX", "RX total", "TX total"])
+        .style(Style::default().bg(theme.table_header).add_modifier(Modifier::BOLD));
+    let visible = area.height.saturating_sub(3) as usize;
+    let rows = networks
+        .iter()
+        .enumerate()
+        .skip(scroll_offset(0, selected, networks.len(), visible))
+        .map(|(i, net)| {
+            let style = if i == selected {
+                Style::default().bg(theme.selected).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                net.name.clone(),
+                format_bit_rate(net.rx_rate as u64),
+                format_bit_rate(net.tx_rate as u64),
+                format_bytes(net.rx_bytes),
+                format_bytes(net.tx_bytes),
+            ])
+            .style(style)
+        });
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Network (i next interface)"))
+        .widths(&[
+            Constraint::Min(10),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ])
+        .column_spacing(1);
+    f.render_widget(table, chunks[0]);
+
+    let net = &networks[selected];
+    let Some(history) = monitor.get_network_history(&net.name) else {
+        return Ok(());
+    };
+    let sparkline_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    for (rates, direction, rate, color, chunk) in [
+        (&history.rx, "RX", net.rx_rate, Color::Green, sparkline_chunks[0]),
+        (&history.tx, "TX", net.tx_rate, Color::Blue, sparkline_chunks[1]),
+    ] {
+        let sparkline_data: Vec<u64> = rates.iter().map(|&rate| rate as u64).collect();
+        let sparkline = Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{} {} {}", net.name, direction, format_bit_rate(rate as u64))),
+            )
+            .data(&sparkline_data)
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, chunk);
+    }
+    Ok(())
+}
+
+// a text bar for table cells, where a Gauge widget doesn't fit
+fn usage_bar(percent: f64, width: usize) -> Line<'static> {
+    let filled = ((percent / 100.0 * width as f64).round() as usize).min(width);
+    let color = match percent as u16 {
+        0..=75 => Color::Green,
+        76..=90 => Color::Yellow,
+        _ => Color::Red,
+    };
+    Line::from(vec![
+        Span::styled("█".repeat(filled), Style::default().fg(color)),
+        Span::styled("░".repeat(width - filled), Style::default().fg(Color::DarkGray)),
+        Span::raw(format!(" {:>3.0}%", percent)),
+    ])
+}
+
+fn draw_disk_stats(f: &mut Frame, area: Rect, monitor: &SystemMonitor, theme: &Theme) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+    let disks = monitor.get_disk_info();
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(area);
+
+    let header = Row::new(["Device", "Mount", "Usage", "Used", "Total", "Read", "Write"])
+        .style(Style::default().bg(theme.table_header).add_modifier(Modifier::BOLD));
+    let rows = disks.iter().map(|disk| {
+        let percent = if disk.total == 0 { 0.0 } else { disk.used as f64 / disk.total as f64 * 100.0 };
+        Row::new(vec![
+            Cell::from(disk.name.as_str()),
+            Cell::from(disk.mount.as_str()),
+            Cell::from(usage_bar(percent, 10)),
+            Cell::from(format_bytes(disk.used)),
+            Cell::from(format_bytes(disk.total)),
+            Cell::from(format_rate(disk.read_rate as u64)),
+            Cell::from(format_rate(disk.write_rate as u64)),
+        ])
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(format!("Disks ({} mounted)", disks.len())))
+        .widths(&[
+            Constraint::Min(10),
+            Constraint::Min(20),
+            Constraint::Length(15),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ])
+        .column_spacing(1);
+    f.render_widget(table, chunks[0]);
+
+    let sparkline_data: Vec<u64> = monitor.get_disk_io_history().iter().map(|&rate| rate as u64).collect();
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Disk I/O ↓ {} ↑ {}",
+            format_rate(monitor.get_total_disk_read_rate()),
+            format_rate(monitor.get_total_disk_write_rate())
+        )))
+        .data(&sparkline_data)
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(sparkline, chunks[1]);
+    Ok(())
+}
+
+fn draw_container_table(f: &mut Frame, area: Rect, monitor: &SystemMonitor, theme: &Theme) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    if let Some(error) = monitor.get_container_error() {
+        let message = Paragraph::new(Line::from(Span::styled(error, Style::default().fg(Color::Red))))
+            .block(Block::default().borders(Borders::ALL).title("Containers (C to go back)"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(message, area);
+        return Ok(());
+    }
+
+    let containers = monitor.get_container_list();
+    let header = Row::new(["ID", "Name", "Status", "CPU%", "Memory"])
+        .style(Style::default().bg(theme.table_header).add_modifier(Modifier::BOLD))
+        .height(1)
+        .bottom_margin(1);
+    let rows = containers.iter().map(|container| {
+        Row::new(vec![
+            container.id.clone(),
+            container.name.clone(),
+            container.status.clone(),
+            format!("{:.1}", container.cpu_percent),
+            format_bytes(container.memory_bytes),
+        ])
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Containers ({} running, C to go back)", containers.len())),
+        )
+        .widths(&[
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(24),
+            Constraint::Length(8),
+            Constraint::Length(10),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(table, area);
+    Ok(())
+}
+
+fn draw_swap_table(
+    f: &mut Frame,
+    area: Rect,
+    processes: &[ProcessInfo],
+    selected_process: usize,
+    scroll: usize,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let header_cells = ["PID", "Name", "Swap", "Memory", "Mem%"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows = processes.iter().enumerate().skip(scroll).map(|(i, process)| {
+        let style = if i == selected_process {
+            Style::default().bg(theme.selected).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        Row::new(vec![
+            process.pid.to_string(),
+            process.name.clone(),
+            format_bytes(process.swap_bytes),
+            format_bytes(process.memory),
+            format!("{:.2}", process.memory_percent),
+        ])
+        .style(style)
+    });
+
+    let total_swap: u64 = processes.iter().map(|p| p.swap_bytes).sum();
+    let swap_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Swap Users ({} in swap, X to go back)", format_bytes(total_swap)))
+        )
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(swap_table, area);
+    Ok(())
+}
+
+fn draw_process_detail(
+    f: &mut Frame,
+    area: Rect,
+    pid: u32,
+    process: Option<&ProcessInfo>,
+    details: &ProcessDetails,
+    quota: Option<&CgroupQuota>,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let label = Style::default().fg(theme.accent);
+    // the inner width left after the borders and the "CWD: " label
+    let cwd_width = (area.width as usize).saturating_sub(2 + "CWD: ".len());
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+
+    let text = match process {
+        Some(process) => vec![
+            Line::from(vec![Span::styled("Name: ", label), Span::raw(process.name.as_str())]),
+            Line::from(vec![Span::styled("PID: ", label), Span::raw(process.pid.to_string())]),
+            Line::from(vec![Span::styled("Parent PID: ", label), Span::raw(process.ppid.to_string())]),
+            Line::from(vec![Span::styled("User: ", label), Span::raw(process.user.as_str())]),
+            Line::from(vec![
+                Span::styled("Command: ", label),
+                Span::raw(details.command_line.as_deref().unwrap_or("n/a")),
+            ]),
+            Line::from(vec![
+                Span::styled("CWD: ", label),
+                Span::raw(details.working_dir.as_ref().map_or("n/a".to_string(), |cwd| {
+                    truncate_left(&cwd.display().to_string(), cwd_width)
+                })),
+            ]),
+            Line::from(vec![
+                Span::styled("Status: ", label),
+                Span::raw(process.state.map_or("?".to_string(), |state| state.to_string())),
+                Span::styled(
+                    if process.hung { "  ⚠ stuck in uninterruptible sleep" } else { "" },
+                    Style::default().fg(Color::Red),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Status changed: ", label),
+                Span::raw(match process.state_changed_at {
+                    Some(at) => format!("{}s ago", at.elapsed().as_secs()),
+                    None => "not since monitoring started".to_string(),
+                }),
+            ]),
+            Line::from(vec![
+                Span::styled("Capabilities: ", label),
+                Span::raw(match process.capabilities.map(|caps| caps.effective_names()) {
+                    Some(names) if names.is_empty() => "none".to_string(),
+                    Some(names) => names.join(", "),
+                    None => "n/a".to_string(),
+                }),
+            ]),
+            Line::from(vec![
+                Span::styled("Seccomp: ", label),
+                match details.seccomp_mode {
+                    Some(SeccompMode::Disabled) => Span::styled("disabled ⚠", Style::default().fg(Color::Yellow)),
+                    Some(mode) => Span::styled(format!("{} ✓", mode.as_str()), Style::default().fg(Color::Green)),
+                    None => Span::raw("n/a"),
+                },
+            ]),
+            Line::from(vec![
+                Span::styled("Children: ", label),
+                Span::raw(format!("{} direct, {} total", details.children, details.descendants)),
+            ]),
+            Line::from(vec![
+                Span::styled("Started: ", label),
+                Span::raw(format_age(now.saturating_sub(process.start_time))),
+            ]),
+            Line::from(vec![
+                Span::styled("Nice: ", label),
+                Span::styled(process.nice.to_string(), Style::default().fg(nice_level_color(process.nice))),
+            ]),
+            Line::from(vec![Span::styled("Threads: ", label), Span::raw(process.threads.to_string())]),
+            Line::from(vec![
+                Span::styled("CPU: ", label),
+                Span::raw(format!("{:.1}%", process.cpu_usage)),
+            ]),
+            Line::from(vec![
+                Span::styled("Memory: ", label),
+                Span::raw(format!("{} ({:.2}%)", format_bytes(process.memory), process.memory_percent)),
+            ]),
+            Line::from(vec![
+                Span::styled("Memory efficiency: ", label),
+                Span::raw(format!(
+                    "{:.0}% ({} resident of {} virtual)",
+                    process.virtual_memory_ratio() * 100.0,
+                    format_bytes(process.memory),
+                    format_bytes(process.virtual_memory)
+                )),
+            ]),
+            Line::from(vec![
+                Span::styled("GPU: ", label),
+                Span::raw(match (process.gpu_usage, process.gpu_memory) {
+                    (Some(usage), Some(vram)) => format!("{:.1}%, {} VRAM", usage, format_bytes(vram)),
+                    (None, Some(vram)) => format!("{} VRAM", format_bytes(vram)),
+                    _ => "n/a".to_string(),
+                }),
+            ]),
+            Line::from(vec![
+                Span::styled("OOM Score: ", label),
+                Span::raw(process.oom_score.map_or("n/a".to_string(), |score| score.to_string())),
+            ]),
+            Line::from(vec![
+                Span::styled("Page faults: ", label),
+                Span::raw(format!("{}/s minor, ", format_count(process.minor_faults_per_sec))),
+                Span::styled(
+                    format!("{}/s major", format_count(process.major_faults_per_sec)),
+                    Style::default().fg(if process.major_faults_per_sec > 100 { Color::Red } else { Color::Reset }),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Disk I/O: ", label),
+                Span::raw(format!(
+                    "{} read, {} written in total ({} / {} now)",
+                    format_bytes(details.disk_read_total),
+                    format_bytes(details.disk_write_total),
+                    format_rate(process.disk_read_rate),
+                    format_rate(process.disk_write_rate)
+                )),
+            ]),
+            Line::from(vec![
+                Span::styled("I/O Priority: ", label),
+                Span::raw(process.io_priority.map_or("n/a".to_string(), |priority| priority.to_string())),
+            ]),
+            Line::from(vec![
+                Span::styled("Affinity: ", label),
+                Span::raw(
+                    process
+                        .cpu_affinity
+                        .as_deref()
+                        .map_or("n/a".to_string(), |cpus| format!("CPUs {}", format_cpu_list(cpus))),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("NUMA node: ", label),
+                Span::raw(process.numa_node.map_or("n/a".to_string(), |node| node.to_string())),
+                if process.numa_cross_node {
+                    Span::styled(" (running on another node ⚠)", Style::default().fg(CROSS_NUMA_COLOR))
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ],
+        None => vec![Line::from(Span::styled(
+            format!("Process {} has exited", pid),
+            Style::default().fg(Color::Red),
+        ))],
+    };
+
+    let detail = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Process {} (Esc close, O oom_score_adj, A affinity, I I/O priority)", pid))
+        )
+        .wrap(Wrap { trim: false });
+
+    // limited cgroups get gauges relative to their quota below the text
+    let cpu_limit = process.zip(quota.and_then(|q| q.cpu_max));
+    let memory_limit = quota.and_then(|q| q.memory_max.zip(q.memory_current));
+    let gauge_count = cpu_limit.is_some() as u16 + memory_limit.is_some() as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(gauge_count * 3)])
+        .split(area);
+
+    f.render_widget(detail, chunks[0]);
+
+    let gauge_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(3); gauge_count as usize])
+        .split(chunks[1]);
+    let mut gauge_areas = gauge_chunks.iter();
+
+    if let (Some((process, cpu_max)), Some(area)) = (cpu_limit, gauge_areas.next()) {
+        // cpu_usage is in percent of one core, cpu_max in cores
+        let percent = (process.cpu_usage / (cpu_max * 100.0) * 100.0).clamp(0.0, 100.0);
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Cgroup CPU quota"))
+            .gauge_style(Style::default().fg(quota_color(percent as u16)))
+            .percent(percent as u16)
+            .label(format!("{:.1}% of {:.2} cores", percent, cpu_max));
+        f.render_widget(gauge, *area);
+    }
+
+    if let (Some((memory_max, memory_current)), Some(area)) = (memory_limit, gauge_areas.next()) {
+        let percent = (memory_current as f64 / memory_max as f64 * 100.0).clamp(0.0, 100.0);
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Cgroup memory limit"))
+            .gauge_style(Style::default().fg(quota_color(percent as u16)))
+            .percent(percent as u16)
+            .label(format!(
+                "{:.1}% ({} / {})",
+                percent,
+                format_bytes(memory_current),
+                format_bytes(memory_max)
+            ));
+        f.render_widget(gauge, *area);
+    }
+    Ok(())
+}
+
+fn quota_color(percent: u16) -> Color {
+    match percent {
+        0..=60 => Color::Green,
+        61..=85 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn draw_follow_tree(
+    f: &mut Frame,
+    area: Rect,
+    processes: &[ProcessInfo],
+    depths: &[usize],
+    scroll: usize,
+    follow: &FollowState,
+    state: &AppState,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+    let (selected_process, theme) = (state.selected, &state.theme);
+
+    let header_cells = ["PID", "Name", "CPU%", "Memory", "Mem%"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows = depths.iter().zip(processes).enumerate().skip(scroll).map(|(i, (depth, process))| {
+        let style = if i == selected_process {
+            Style::default().bg(theme.selected).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        let name = if *depth == 0 {
+            process.name.clone()
+        } else {
+            format!("{}└─ {}", "  ".repeat(depth - 1), process.name)
+        };
+
+        Row::new(vec![
+            process.pid.to_string(),
+            name,
+            format!("{:.1}", process.cpu_usage),
+            format_bytes(process.memory),
+            format!("{:.2}", process.memory_percent),
+        ])
+        .style(style)
+    });
+
+    let follow_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Following PID {} ({} live processes)", follow.root_pid, processes.len()))
+        )
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(follow_table, area);
+    Ok(())
+}
+
+fn draw_follow_summary(f: &mut Frame, area: Rect, follow: &FollowState) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let mut text = vec![Line::from(Span::styled(
+        format!("Process {} exited", follow.root_pid),
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    ))];
+
+    if let Some(last) = &follow.last_root_info {
+        text.push(Line::from(format!("Name: {}", last.name)));
+        text.push(Line::from(format!("Last CPU: {:.1}%", last.cpu_usage)));
+        text.push(Line::from(format!(
+            "Last memory: {} ({:.2}%)",
+            format_bytes(last.memory),
+            last.memory_percent
+        )));
+    }
+    text.push(Line::from(format!("Processes followed: {}", follow.watched_count())));
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(
+        "Press any key to exit",
+        Style::default().fg(Color::Yellow),
+    )));
+
+    let summary = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Follow finished"))
+        .alignment(Alignment::Center);
+
+    f.render_widget(summary, area);
+    Ok(())
+}
+
+fn draw_network_view(
+    f: &mut Frame,
+    area: Rect,
+    monitor: &SystemMonitor,
+    sort_order: &SortOrder,
+    tab: PortsTab,
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(5)])
+        .split(area);
+
+    let tabs = Tabs::new(vec!["TCP/UDP", "Unix"])
+        .select(match tab {
+            PortsTab::Inet => 0,
+            PortsTab::Unix => 1,
+        })
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    f.render_widget(tabs, chunks[0]);
+
+    let tcp = monitor.get_tcp_stats();
+    let tcp_summary = Paragraph::new(Line::from(vec![
+        Span::raw(format!(
+            "TCP: {} established | {} opens/s out, {} in | {} failed/s | {} resets/s | ",
+            tcp.established,
+            tcp.active_opens_per_sec,
+            tcp.passive_opens_per_sec,
+            tcp.attempt_fails_per_sec,
+            tcp.estab_resets_per_sec
+        )),
+        Span::styled(
+            format!("{} retrans/s", tcp.retrans_segs_per_sec),
+            Style::default().fg(if tcp.retrans_segs_per_sec > 0 { Color::Yellow } else { Color::Reset }),
+        ),
+    ]))
+    .alignment(Alignment::Right);
+    f.render_widget(tcp_summary, chunks[0]);
+
+    if !SOCKETS_SUPPORTED {
+        let message = Paragraph::new("Listing ports and sockets is only supported on Linux")
+            .block(Block::default().borders(Borders::ALL))
+            .alignment(Alignment::Center);
+        f.render_widget(message, chunks[1]);
+        return Ok(());
+    }
+
+    let processes = monitor.get_processes(sort_order);
+    match tab {
+        PortsTab::Inet => draw_ports_panel(f, chunks[1], monitor.get_listening_ports(), &processes, theme),
+        PortsTab::Unix => draw_unix_sockets_panel(f, chunks[1], monitor.get_unix_sockets(), &processes, theme),
+    }
+}
+
+fn process_name(processes: &[ProcessInfo], pid: u32) -> String {
+    processes
+        .iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.name.clone())
+        .unwrap_or_default()
+}
+
+fn draw_ports_panel(
+    f: &mut Frame,
+    area: Rect,
+    ports: &[NetworkPort],
+    processes: &[ProcessInfo],
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let header_cells = ["Port", "Protocol", "State", "PID", "Process Name"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    // ports arrive sorted by port number from the monitor
+    let rows = ports.iter().map(|port| {
+        let (pid, name) = match port.pid {
+            Some(pid) => (pid.to_string(), process_name(processes, pid)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        Row::new(vec![
+            port.port.to_string(),
+            port.protocol.as_str().to_string(),
+            port.state.clone(),
+            pid,
+            name,
+        ])
+    });
+
+    let ports_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Listening Ports ({})", ports.len()))
+        )
+        .widths(&[
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Min(20),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(ports_table, area);
+    Ok(())
+}
+
+fn draw_unix_sockets_panel(
+    f: &mut Frame,
+    area: Rect,
+    sockets: &[UnixSocket],
+    processes: &[ProcessInfo],
+    theme: &Theme,
+) -> Result<()> {
+    if too_small(area, MIN_HEIGHT) {
+        return Ok(());
+    }
+
+    let header_cells = ["PID", "Process Name", "State", "Path"]
+        .iter()
+        .map(|h| Span::styled(*h, Style::default().add_modifier(Modifier::BOLD)));
+
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(theme.table_header))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows = sockets.iter().map(|socket| {
+        Row::new(vec![
+            socket.pid.to_string(),
+            process_name(processes, socket.pid),
+            socket.state_name().to_string(),
+            socket.path.clone().unwrap_or_else(|| "-".to_string()),
+        ])
+    });
+
+    let sockets_table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Unix Sockets ({})", sockets.len()))
+        )
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Length(20),
+            Constraint::Length(14),
+            Constraint::Min(20),
+        ])
+        .column_spacing(1);
+
+    f.render_widget(sockets_table, area);
+    Ok(())
+}
+
+fn search_match_style() -> Style {
+    Style::default().fg(Color::Black).bg(Color::Yellow)
+}
+
+// each mode gets its own color so it's clear at a glance what the text does
+fn filter_prompt(mode: &FilterMode) -> Span<'static> {
+    let color = match mode {
+        FilterMode::Exclude(_) => Color::Magenta,
+        FilterMode::Regex(_) => Color::Cyan,
+        FilterMode::Search(_) | FilterMode::None => Color::Yellow,
+    };
+    Span::styled(format!("{}: ", mode.label()), Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+fn filter_error_span(state: &AppState) -> Span<'_> {
+    match &state.filter_error {
+        Some(err) => Span::styled(format!("  {}", err), Style::default().fg(Color::Red)),
+        None => Span::raw(""),
+    }
+}
+
+fn draw_footer(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) -> Result<()> {
+    if too_small(area, BAR_HEIGHT) {
+        return Ok(());
+    }
+
+    let prompt_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut footer_text = match (&state.input, state.status_message.as_deref(), state.filter_mode.text()) {
+        (Some(input), _, _) => vec![Line::from(vec![
+            Span::styled(input.label(), prompt_style),
+            Span::raw(input.buffer.as_str()),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+        ])],
+        (None, _, Some(filter)) if state.mode == AppMode::FilterEntry => vec![Line::from(vec![
+            filter_prompt(&state.filter_mode),
+            Span::raw(filter),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+            filter_error_span(state),
+        ])],
+        (None, Some(message), _) => vec![Line::from(Span::styled(message, Style::default().fg(theme.accent)))],
+        (None, None, Some(filter)) => vec![Line::from(vec![
+            filter_prompt(&state.filter_mode),
+            Span::raw(filter),
+            Span::raw(" (Esc to clear)"),
+            filter_error_span(state),
+        ])],
+        (None, None, None) => vec![
+            Line::from("Controls: ↑/↓ or j/k (navigate) | PgUp/PgDn/Home/End (jump) | K (send signal) | v (mark for batch kill) | N (renice) | H (hide process) | c (sort by CPU) | m (sort by memory) | o (sort by OOM score) | M (sort by major faults) | e (sort by memory efficiency) | h (sort by health) | t (sort by threads) | r/w (sort by disk read/write) | u (sort by user) | s (sort by start time) | n (sort by nice) | g (sort by GPU) | T (tree) | X (swap users) | C (containers) | D (diff since now) | Space (pause) | F2 (follow top process) | i (network interfaces) | Tab (stats/network/disks/processes only) | p (priority processes) | ^K (kernel threads) | f (jump to name) | / (search) | ! (filter) | ` (regex filter) | Enter (details) | P (ports/unix sockets) | S (swappiness) | L (layout) | l (color legend) | : (command) | q (quit)")
+        ],
+    };
+
+    if state.paused {
+        footer_text[0].spans.insert(
+            0,
+            Span::styled("PAUSED ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        );
+    }
 
-    if debug_mode {
+    if state.debug {
         footer_text.push(Line::from(Span::styled(
             "DEBUG MODE ACTIVE", 
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
@@ -228,4 +2123,5 @@ fn draw_footer(f: &mut Frame, area: Rect, debug_mode: bool) {
         .wrap(Wrap { trim: true });
 
     f.render_widget(footer, area);
+    Ok(())
 }
\ No newline at end of file